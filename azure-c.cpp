@@ -4,6 +4,13 @@
 
 #include "azure-c.h"
 #include "mozilla/gfx/2D.h"
+#include "mozilla/gfx/Blur.h"
+#include "ImageScaling.h"
+#include "Scale.h"
+#include "ScaledFontBase.h"
+#include "Swizzle.h"
+#include "DrawEventPlayer.h"
+#include "DrawEventRecorder.h"
 
 #include <assert.h>
 #include <string.h>
@@ -18,9 +25,16 @@ static AzIntSize IntSizeToC(gfx::IntSize src) {
     return dst;
 }
 
+static AzIntRect IntRectToC(gfx::IntRect src) {
+    AzIntRect dst;
+    memcpy(&dst, &src, sizeof(dst));
+    return dst;
+}
+
 
 #define CHECK_SIZE(name) assert(sizeof(Az##name) == sizeof(gfx::name))
 #define CHECK_ENUM(name) assert((int)AZ_##name == (int)gfx::name)
+#define CHECK_EVENT_TYPE(name) assert((int)AZ_EVENT_##name == (int)gfx::RecordedEvent::name)
 
 extern "C"
 void AzSanityCheck() {
@@ -40,6 +54,7 @@ void AzSanityCheck() {
     CHECK_SIZE(Glyph);
     CHECK_SIZE(GlyphBuffer);
     CHECK_SIZE(NativeFont);
+    CHECK_SIZE(RecordedEventDescription);
 
     CHECK_ENUM(SURFACE_DATA);
     CHECK_ENUM(SURFACE_D2D1_BITMAP);
@@ -79,6 +94,11 @@ void AzSanityCheck() {
     CHECK_ENUM(NATIVE_FONT_SKIA_FONT_FACE);
     CHECK_ENUM(NATIVE_FONT_CAIRO_FONT_FACE);
 
+    CHECK_ENUM(FONT_HINTING_NONE);
+    CHECK_ENUM(FONT_HINTING_LIGHT);
+    CHECK_ENUM(FONT_HINTING_NORMAL);
+    CHECK_ENUM(FONT_HINTING_FULL);
+
     CHECK_ENUM(OP_OVER);
     CHECK_ENUM(OP_ADD);
     CHECK_ENUM(OP_ATOP);
@@ -113,6 +133,7 @@ void AzSanityCheck() {
 
     CHECK_ENUM(FILTER_LINEAR);
     CHECK_ENUM(FILTER_POINT);
+    CHECK_ENUM(FILTER_GOOD);
 
     CHECK_ENUM(PATTERN_COLOR);
     CHECK_ENUM(PATTERN_SURFACE);
@@ -131,10 +152,62 @@ void AzSanityCheck() {
     CHECK_ENUM(SAMPLING_UNBOUNDED);
     CHECK_ENUM(SAMPLING_BOUNDED);
 
+    CHECK_ENUM(PIXEL_FORMAT_B8G8R8A8);
+    CHECK_ENUM(PIXEL_FORMAT_B8G8R8X8);
+    CHECK_ENUM(PIXEL_FORMAT_R5G6B5);
+    CHECK_ENUM(PIXEL_FORMAT_A8);
+    CHECK_ENUM(PIXEL_FORMAT_R8G8B8A8);
+    CHECK_ENUM(PIXEL_FORMAT_R8G8B8X8);
+    CHECK_ENUM(PIXEL_FORMAT_B8G8R8A8_UNPREMULTIPLIED);
+    CHECK_ENUM(PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED);
+
+    CHECK_ENUM(RESIZE_GOOD);
+    CHECK_ENUM(RESIZE_BETTER);
+    CHECK_ENUM(RESIZE_BEST);
+    CHECK_ENUM(RESIZE_BOX);
+    CHECK_ENUM(RESIZE_HAMMING1);
+    CHECK_ENUM(RESIZE_LANCZOS2);
+    CHECK_ENUM(RESIZE_LANCZOS3);
+
     assert((int)AZ_eSideTop == (int)css::eSideTop);
     assert((int)AZ_eSideRight == (int)css::eSideRight);
     assert((int)AZ_eSideBottom == (int)css::eSideBottom);
     assert((int)AZ_eSideLeft == (int)css::eSideLeft);
+
+    CHECK_EVENT_TYPE(DRAWTARGETCREATION);
+    CHECK_EVENT_TYPE(DRAWTARGETDESTRUCTION);
+    CHECK_EVENT_TYPE(FILLRECT);
+    CHECK_EVENT_TYPE(STROKERECT);
+    CHECK_EVENT_TYPE(STROKELINE);
+    CHECK_EVENT_TYPE(CLEARRECT);
+    CHECK_EVENT_TYPE(COPYSURFACE);
+    CHECK_EVENT_TYPE(SETTRANSFORM);
+    CHECK_EVENT_TYPE(PUSHCLIP);
+    CHECK_EVENT_TYPE(PUSHCLIPRECT);
+    CHECK_EVENT_TYPE(POPCLIP);
+    CHECK_EVENT_TYPE(FILL);
+    CHECK_EVENT_TYPE(FILLGLYPHS);
+    CHECK_EVENT_TYPE(MASK);
+    CHECK_EVENT_TYPE(STROKE);
+    CHECK_EVENT_TYPE(DRAWSURFACE);
+    CHECK_EVENT_TYPE(DRAWSURFACEWITHSHADOW);
+    CHECK_EVENT_TYPE(PATHCREATION);
+    CHECK_EVENT_TYPE(PATHDESTRUCTION);
+    CHECK_EVENT_TYPE(SOURCESURFACECREATION);
+    CHECK_EVENT_TYPE(SOURCESURFACEDESTRUCTION);
+    CHECK_EVENT_TYPE(GRADIENTSTOPSCREATION);
+    CHECK_EVENT_TYPE(GRADIENTSTOPSDESTRUCTION);
+    CHECK_EVENT_TYPE(SNAPSHOT);
+    CHECK_EVENT_TYPE(SCALEDFONTCREATION);
+    CHECK_EVENT_TYPE(SCALEDFONTDESTRUCTION);
+
+    CHECK_ENUM(RECORDING_ERROR_NONE);
+    CHECK_ENUM(RECORDING_ERROR_BAD_HEADER);
+    CHECK_ENUM(RECORDING_ERROR_UNSUPPORTED_VERSION);
+    CHECK_ENUM(RECORDING_ERROR_UNKNOWN_EVENT);
+    CHECK_ENUM(RECORDING_ERROR_TRUNCATED);
+    CHECK_ENUM(RECORDING_ERROR_INVALID_EVENT);
+    CHECK_ENUM(RECORDING_ERROR_UNKNOWN_REFERENCE);
 }
 
 
@@ -235,6 +308,18 @@ AzCreateDrawTargetForData(AzBackendType aBackend, unsigned char *aData, AzIntSiz
     return target;
 }
 
+extern "C" AzDrawTargetRef
+AzCreateRecordingDrawTarget(AzDrawEventRecorderRef aRecorder, AzDrawTargetRef aDrawTarget) {
+    gfx::DrawEventRecorder *gfxRecorder = static_cast<gfx::DrawEventRecorder*>(aRecorder);
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    RefPtr<gfx::DrawTarget> target = gfx::Factory::CreateRecordingDrawTarget(gfxRecorder,
+                                                                             gfxDrawTarget);
+    if (target != NULL) {
+        target->AddRef();
+    }
+    return target;
+}
+
 extern "C" AzDrawTargetRef
 AzCreateSkiaDrawTargetForFBO(AzSkiaSharedGLContextRef aGLContext, AzIntSize *aSize, AzSurfaceFormat aFormat) {
     SkNativeSharedGLContext *sharedGLContext = static_cast<SkNativeSharedGLContext*>(aGLContext);
@@ -378,6 +463,22 @@ AzDrawTargetDrawSurface(AzDrawTargetRef aDrawTarget,
     gfxDrawTarget->DrawSurface(gfxSourceSurface, *gfxDest, *gfxSource, *gfxDrawSurfaceOptions, *gfxDrawOptions);
 }
 
+extern "C" void
+AzDrawTargetMaskSurface(AzDrawTargetRef aDrawTarget,
+                        AzPatternRef aSource,
+                        AzSourceSurfaceRef aMask,
+                        AzPoint *aOffset,
+                        AzDrawOptions *aOptions) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    gfx::Pattern *gfxSource = static_cast<gfx::Pattern*>(aSource);
+    gfx::SourceSurface *gfxMask = static_cast<gfx::SourceSurface*>(aMask);
+    gfx::DrawOptions *gfxDrawOptions = reinterpret_cast<gfx::DrawOptions*>(aOptions);
+    gfx::Matrix gfxMatrix(1, 0, 0, 1, aOffset->x, aOffset->y);
+    gfxDrawTarget->Mask(*gfxSource,
+                        gfx::SurfacePattern(gfxMask, gfx::EXTEND_CLAMP, gfxMatrix),
+                        *gfxDrawOptions);
+}
+
 extern "C" AzSourceSurfaceRef
 AzDrawTargetGetSnapshot(AzDrawTargetRef aDrawTarget) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
@@ -426,6 +527,18 @@ AzSourceSurfaceGetDataSurface(AzSourceSurfaceRef aSurface) {
     return gfxDataSourceSurface;
 }
 
+extern "C" AzDataSourceSurfaceRef
+AzCreateDataSourceSurface(AzIntSize *aSize, AzSurfaceFormat aFormat) {
+    gfx::IntSize *gfxSize = reinterpret_cast<gfx::IntSize*>(aSize);
+    gfx::SurfaceFormat gfxSurfaceFormat = static_cast<gfx::SurfaceFormat>(aFormat);
+    RefPtr<gfx::DataSourceSurface> gfxDataSourceSurface =
+        gfx::Factory::CreateDataSourceSurface(*gfxSize, gfxSurfaceFormat);
+    if (gfxDataSourceSurface != NULL) {
+        gfxDataSourceSurface->AddRef();
+    }
+    return gfxDataSourceSurface;
+}
+
 extern "C" uint8_t *AzDataSourceSurfaceGetData(AzDataSourceSurfaceRef aSurface) {
     gfx::DataSourceSurface *gfxDataSourceSurface = static_cast<gfx::DataSourceSurface*>(aSurface);
     return gfxDataSourceSurface->GetData();
@@ -436,6 +549,26 @@ extern "C" int32_t AzDataSourceSurfaceGetStride(AzDataSourceSurfaceRef aSurface)
     return gfxDataSourceSurface->Stride();
 }
 
+extern "C" bool
+AzConvertPixels(const uint8_t *aSrc, int32_t aSrcStride, AzPixelFormat aSrcFormat,
+                uint8_t *aDst, int32_t aDstStride, AzPixelFormat aDstFormat,
+                AzIntSize *aSize) {
+    gfx::IntSize *gfxSize = reinterpret_cast<gfx::IntSize*>(aSize);
+    return gfx::ConvertPixels(aSrc, aSrcStride, static_cast<gfx::PixelFormat>(aSrcFormat),
+                              aDst, aDstStride, static_cast<gfx::PixelFormat>(aDstFormat),
+                              *gfxSize);
+}
+
+extern "C" bool
+AzScale(const uint8_t *aSrc, AzIntSize *aSrcSize, int32_t aSrcStride,
+        uint8_t *aDst, AzIntSize *aDstSize, int32_t aDstStride,
+        AzSurfaceFormat aFormat, AzResizeMethod aMethod) {
+    return gfx::Scale(const_cast<uint8_t*>(aSrc), aSrcSize->width, aSrcSize->height, aSrcStride,
+                      aDst, aDstSize->width, aDstSize->height, aDstStride,
+                      static_cast<gfx::SurfaceFormat>(aFormat),
+                      static_cast<gfx::ResizeMethod>(aMethod));
+}
+
 extern "C" AzScaledFontRef
 AzCreateScaledFontForNativeFont(AzNativeFont *aNativeFont, AzFloat aSize) {
     gfx::NativeFont *gfxNativeFont = reinterpret_cast<gfx::NativeFont*>(aNativeFont);
@@ -444,12 +577,401 @@ AzCreateScaledFontForNativeFont(AzNativeFont *aNativeFont, AzFloat aSize) {
     return font;
 }
 
+extern "C" AzScaledFontRef
+AzCreateScaledFontForTrueTypeData(const uint8_t *aData, uint32_t aSize,
+                                  uint32_t aFaceIndex, AzFloat aGlyphSize,
+                                  AzFontType aType) {
+    gfx::FontType fontType = static_cast<gfx::FontType>(aType);
+    RefPtr<gfx::ScaledFont> font =
+        gfx::Factory::CreateScaledFontForTrueTypeData(const_cast<uint8_t*>(aData), aSize,
+                                                      aFaceIndex, aGlyphSize, fontType);
+    if (!font) {
+        return NULL;
+    }
+    font->AddRef();
+    return font;
+}
+
+extern "C" void
+AzRetainScaledFont(AzScaledFontRef aFont) {
+    gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
+    gfxFont->AddRef();
+}
+
 extern "C" void
 AzReleaseScaledFont(AzScaledFontRef aFont) {
     gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
     gfxFont->Release();
 }
 
+extern "C" AzPathRef
+AzScaledFontGetPathForGlyphs(AzScaledFontRef aFont, AzGlyphBuffer *aGlyphBuffer,
+                             AzDrawTargetRef aDrawTarget) {
+    gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
+    gfx::GlyphBuffer *gfxGlyphBuffer = reinterpret_cast<gfx::GlyphBuffer*>(aGlyphBuffer);
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    RefPtr<gfx::Path> gfxPath = gfxFont->GetPathForGlyphs(*gfxGlyphBuffer, gfxDrawTarget);
+    if (!gfxPath) {
+        return NULL;
+    }
+    gfxPath->AddRef();
+    return gfxPath;
+}
+
+extern "C" bool
+AzScaledFontCopyGlyphsToBuilder(AzScaledFontRef aFont, AzGlyphBuffer *aGlyphBuffer,
+                                AzPathBuilderRef aPathBuilder) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(aFont);
+    gfx::GlyphBuffer *gfxGlyphBuffer = reinterpret_cast<gfx::GlyphBuffer*>(aGlyphBuffer);
+    gfx::PathBuilder *gfxPathBuilder = static_cast<gfx::PathBuilder*>(aPathBuilder);
+    if (!gfxFont->CanCopyGlyphsToBuilder()) {
+        return false;
+    }
+    gfxFont->CopyGlyphsToBuilder(*gfxGlyphBuffer, gfxPathBuilder);
+    return true;
+}
+
+extern "C" AzFontMetrics
+AzScaledFontGetFontMetrics(AzScaledFontRef aFont) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(aFont);
+    AzFontMetrics metrics;
+    gfxFont->GetFontMetrics(metrics.mAscent, metrics.mDescent, metrics.mLineGap);
+    return metrics;
+}
+
+extern "C" AzFloat
+AzScaledFontGetGlyphAdvance(AzScaledFontRef aFont, uint32_t aGlyph) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(aFont);
+    return gfxFont->GetGlyphAdvance(aGlyph);
+}
+
+extern "C" AzRect
+AzScaledFontGetGlyphBounds(AzScaledFontRef aFont, uint32_t aGlyph) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(aFont);
+    gfx::Rect bounds = gfxFont->GetGlyphBounds(aGlyph);
+    AzRect result = { bounds.x, bounds.y, bounds.width, bounds.height };
+    return result;
+}
+
+extern "C" void
+AzScaledFontGetGlyphIndices(AzScaledFontRef aFont, const uint32_t *aCodepoints,
+                            uint32_t aCount, uint32_t *aGlyphs) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(aFont);
+    gfxFont->GetGlyphIndices(aCodepoints, aCount, aGlyphs);
+}
+
+extern "C" bool
+AzScaledFontGetFontFileData(AzScaledFontRef aFont, AzFontFileDataOutput aDataCallback,
+                            void *aBaton) {
+    gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
+    return gfxFont->GetFontFileData(aDataCallback, aBaton);
+}
+
+extern "C" AzGlyphRenderingOptionsRef
+AzCreateSkiaGlyphRenderingOptions(AzFontHinting aHinting, bool aSubpixelPositioning) {
+    gfx::FontHinting gfxHinting = static_cast<gfx::FontHinting>(aHinting);
+    RefPtr<gfx::GlyphRenderingOptions> options =
+        gfx::Factory::CreateSkiaGlyphRenderingOptions(gfxHinting, aSubpixelPositioning);
+    options->AddRef();
+    return options;
+}
+
+// Returns null on platforms without CoreGraphics.
+extern "C" AzGlyphRenderingOptionsRef
+AzCreateCGGlyphRenderingOptions(bool aSubpixelPositioning) {
+    #ifdef XP_MACOSX
+    RefPtr<gfx::GlyphRenderingOptions> options =
+        gfx::Factory::CreateCGGlyphRenderingOptions(aSubpixelPositioning);
+    options->AddRef();
+    return options;
+    #else
+    return NULL;
+    #endif
+}
+
+extern "C" void
+AzReleaseGlyphRenderingOptions(AzGlyphRenderingOptionsRef aOptions) {
+    gfx::GlyphRenderingOptions *gfxOptions = static_cast<gfx::GlyphRenderingOptions*>(aOptions);
+    gfxOptions->Release();
+}
+
+extern "C" AzDrawEventRecorderRef
+AzCreateDrawEventRecorderForFile(const char *aFilename) {
+    RefPtr<gfx::DrawEventRecorderFile> recorder = new gfx::DrawEventRecorderFile(aFilename);
+    if (!recorder->IsOpen()) {
+        return NULL;
+    }
+    recorder->AddRef();
+    return static_cast<gfx::DrawEventRecorder*>(recorder);
+}
+
+extern "C" AzDrawEventRecorderRef
+AzCreateDrawEventRecorderMemory() {
+    RefPtr<gfx::DrawEventRecorderMemory> recorder = new gfx::DrawEventRecorderMemory();
+    recorder->AddRef();
+    return static_cast<gfx::DrawEventRecorder*>(recorder);
+}
+
+extern "C" void
+AzDrawEventRecorderMemoryTakeData(AzDrawEventRecorderRef aRecorder,
+                                  AzRecordingDataOutput aDataCallback,
+                                  void *aBaton) {
+    gfx::DrawEventRecorderMemory *gfxRecorder =
+        static_cast<gfx::DrawEventRecorderMemory*>(static_cast<gfx::DrawEventRecorder*>(aRecorder));
+    gfxRecorder->TakeRecording(aDataCallback, aBaton);
+}
+
+extern "C" void
+AzRetainDrawEventRecorder(AzDrawEventRecorderRef aRecorder) {
+    gfx::DrawEventRecorder *gfxRecorder = static_cast<gfx::DrawEventRecorder*>(aRecorder);
+    gfxRecorder->AddRef();
+}
+
+extern "C" void
+AzReleaseDrawEventRecorder(AzDrawEventRecorderRef aRecorder) {
+    gfx::DrawEventRecorder *gfxRecorder = static_cast<gfx::DrawEventRecorder*>(aRecorder);
+    gfxRecorder->Release();
+}
+
+extern "C" void
+AzSetGlobalEventRecorder(AzDrawEventRecorderRef aRecorder) {
+    gfx::DrawEventRecorder *gfxRecorder = static_cast<gfx::DrawEventRecorder*>(aRecorder);
+    gfx::Factory::SetGlobalEventRecorder(gfxRecorder);
+}
+
+extern "C" AzDrawEventPlayerRef
+AzCreateDrawEventPlayer(AzBackendType aBackend) {
+    gfx::BackendType backendType = static_cast<gfx::BackendType>(aBackend);
+    return new gfx::DrawEventPlayer(backendType);
+}
+
+extern "C" void
+AzReleaseDrawEventPlayer(AzDrawEventPlayerRef aPlayer) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    delete gfxPlayer;
+}
+
+extern "C" bool
+AzDrawEventPlayerPlay(AzDrawEventPlayerRef aPlayer,
+                      const uint8_t *aData,
+                      size_t aDataLen) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    std::string recording(reinterpret_cast<const char*>(aData), aDataLen);
+    std::istringstream stream(recording, std::ios_base::in | std::ios_base::binary);
+    return gfxPlayer->PlayStream(stream);
+}
+
+// A streambuf which reads a recording through an AzRecordingDataInput
+// callback. It can't seek, so the player can't check event lengths against
+// the rest of the recording before reading them.
+class RecordingInputBuffer : public std::streambuf {
+public:
+    RecordingInputBuffer(AzRecordingDataInput aDataCallback, void *aBaton)
+        : mDataCallback(aDataCallback), mBaton(aBaton) {}
+
+protected:
+    virtual int_type underflow() {
+        size_t length = mDataCallback(mBuffer, sizeof(mBuffer), mBaton);
+        if (length == 0) {
+            return traits_type::eof();
+        }
+        char *buffer = reinterpret_cast<char*>(mBuffer);
+        setg(buffer, buffer, buffer + length);
+        return traits_type::to_int_type(*gptr());
+    }
+
+private:
+    AzRecordingDataInput mDataCallback;
+    void *mBaton;
+    uint8_t mBuffer[4096];
+};
+
+extern "C" bool
+AzDrawEventPlayerPlayInput(AzDrawEventPlayerRef aPlayer,
+                           AzRecordingDataInput aDataCallback,
+                           void *aBaton) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    RecordingInputBuffer buffer(aDataCallback, aBaton);
+    std::istream stream(&buffer);
+    return gfxPlayer->PlayStream(stream);
+}
+
+extern "C" AzRecordingError
+AzDrawEventPlayerGetError(AzDrawEventPlayerRef aPlayer) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    return static_cast<AzRecordingError>(gfxPlayer->GetError());
+}
+
+extern "C" size_t
+AzDrawEventPlayerGetDrawTargetCount(AzDrawEventPlayerRef aPlayer) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    return gfxPlayer->GetDrawTargetCount();
+}
+
+extern "C" AzDrawTargetRef
+AzDrawEventPlayerGetDrawTarget(AzDrawEventPlayerRef aPlayer,
+                               size_t aIndex) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    if (aIndex >= gfxPlayer->GetDrawTargetCount()) {
+        return NULL;
+    }
+    gfx::DrawTarget *gfxDrawTarget = gfxPlayer->GetDrawTarget(aIndex);
+    gfxDrawTarget->AddRef();
+    return gfxDrawTarget;
+}
+
+extern "C" AzRecordedEventReaderRef
+AzCreateRecordedEventReader(const uint8_t *aData,
+                            size_t aDataLen) {
+    std::string recording(reinterpret_cast<const char*>(aData), aDataLen);
+    return new gfx::RecordedEventReader(recording);
+}
+
+extern "C" void
+AzReleaseRecordedEventReader(AzRecordedEventReaderRef aReader) {
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    delete gfxReader;
+}
+
+extern "C" bool
+AzRecordedEventReaderNext(AzRecordedEventReaderRef aReader,
+                          AzRecordedEventDescription *aDescription) {
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    if (!gfxReader->Next()) {
+        return false;
+    }
+    gfx::RecordedEventDescription *gfxDescription =
+        reinterpret_cast<gfx::RecordedEventDescription*>(aDescription);
+    gfxReader->CurrentEvent()->GetDescription(*gfxDescription);
+    return true;
+}
+
+extern "C" bool
+AzRecordedEventReaderHasFailed(AzRecordedEventReaderRef aReader) {
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    return gfxReader->HasFailed();
+}
+
+extern "C" AzRecordingError
+AzRecordedEventReaderGetError(AzRecordedEventReaderRef aReader) {
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    return static_cast<AzRecordingError>(gfxReader->GetError());
+}
+
+extern "C" const char *
+AzRecordedEventReaderGetEventInfo(AzRecordedEventReaderRef aReader) {
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    return gfxReader->CurrentEventInfo().c_str();
+}
+
+extern "C" bool
+AzDrawEventPlayerPlayCurrentEvent(AzDrawEventPlayerRef aPlayer,
+                                  AzRecordedEventReaderRef aReader) {
+    gfx::DrawEventPlayer *gfxPlayer = static_cast<gfx::DrawEventPlayer*>(aPlayer);
+    gfx::RecordedEventReader *gfxReader = static_cast<gfx::RecordedEventReader*>(aReader);
+    if (!gfxReader->CurrentEvent()) {
+        return false;
+    }
+    return gfxPlayer->PlayEvent(gfxReader->CurrentEvent());
+}
+
+extern "C" AzAlphaBoxBlurRef
+AzCreateAlphaBoxBlur(AzRect *aRect,
+                     AzIntSize *aSpreadRadius,
+                     AzIntSize *aBlurRadius,
+                     AzRect *aDirtyRect,
+                     AzRect *aSkipRect) {
+    gfx::Rect *gfxRect = reinterpret_cast<gfx::Rect*>(aRect);
+    gfx::IntSize *gfxSpreadRadius = reinterpret_cast<gfx::IntSize*>(aSpreadRadius);
+    gfx::IntSize *gfxBlurRadius = reinterpret_cast<gfx::IntSize*>(aBlurRadius);
+    gfx::Rect *gfxDirtyRect = reinterpret_cast<gfx::Rect*>(aDirtyRect);
+    gfx::Rect *gfxSkipRect = reinterpret_cast<gfx::Rect*>(aSkipRect);
+    return new gfx::AlphaBoxBlur(*gfxRect, *gfxSpreadRadius, *gfxBlurRadius,
+                                 gfxDirtyRect, gfxSkipRect);
+}
+
+extern "C" void
+AzReleaseAlphaBoxBlur(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    delete gfxBlur;
+}
+
+extern "C" uint8_t *
+AzAlphaBoxBlurGetData(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    return gfxBlur->GetData();
+}
+
+extern "C" AzIntSize
+AzAlphaBoxBlurGetSize(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    return IntSizeToC(gfxBlur->GetSize());
+}
+
+extern "C" int32_t
+AzAlphaBoxBlurGetStride(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    return gfxBlur->GetStride();
+}
+
+extern "C" AzIntRect
+AzAlphaBoxBlurGetRect(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    return IntRectToC(gfxBlur->GetRect());
+}
+
+extern "C" void
+AzAlphaBoxBlurBlur(AzAlphaBoxBlurRef aBlur) {
+    gfx::AlphaBoxBlur *gfxBlur = static_cast<gfx::AlphaBoxBlur*>(aBlur);
+    gfxBlur->Blur();
+}
+
+extern "C" AzIntSize
+AzAlphaBoxBlurCalculateBlurRadius(AzPoint *aStandardDeviation) {
+    gfx::Point *gfxStandardDeviation = reinterpret_cast<gfx::Point*>(aStandardDeviation);
+    return IntSizeToC(gfx::AlphaBoxBlur::CalculateBlurRadius(*gfxStandardDeviation));
+}
+
+extern "C" AzImageHalfScalerRef
+AzCreateImageHalfScaler(const uint8_t *aData,
+                        int32_t aStride,
+                        AzIntSize *aSize) {
+    gfx::IntSize *gfxSize = reinterpret_cast<gfx::IntSize*>(aSize);
+    // The scaler only reads from the original data.
+    return new gfx::ImageHalfScaler(const_cast<uint8_t*>(aData), aStride, *gfxSize);
+}
+
+extern "C" void
+AzReleaseImageHalfScaler(AzImageHalfScalerRef aScaler) {
+    gfx::ImageHalfScaler *gfxScaler = static_cast<gfx::ImageHalfScaler*>(aScaler);
+    delete gfxScaler;
+}
+
+extern "C" void
+AzImageHalfScalerScaleForSize(AzImageHalfScalerRef aScaler, AzIntSize *aSize) {
+    gfx::ImageHalfScaler *gfxScaler = static_cast<gfx::ImageHalfScaler*>(aScaler);
+    gfx::IntSize *gfxSize = reinterpret_cast<gfx::IntSize*>(aSize);
+    gfxScaler->ScaleForSize(*gfxSize);
+}
+
+extern "C" uint8_t *
+AzImageHalfScalerGetScaledData(AzImageHalfScalerRef aScaler) {
+    gfx::ImageHalfScaler *gfxScaler = static_cast<gfx::ImageHalfScaler*>(aScaler);
+    return gfxScaler->GetScaledData();
+}
+
+extern "C" AzIntSize
+AzImageHalfScalerGetSize(AzImageHalfScalerRef aScaler) {
+    gfx::ImageHalfScaler *gfxScaler = static_cast<gfx::ImageHalfScaler*>(aScaler);
+    return IntSizeToC(gfxScaler->GetSize());
+}
+
+extern "C" uint32_t
+AzImageHalfScalerGetStride(AzImageHalfScalerRef aScaler) {
+    gfx::ImageHalfScaler *gfxScaler = static_cast<gfx::ImageHalfScaler*>(aScaler);
+    return gfxScaler->GetStride();
+}
+
 extern "C" void
 AzDrawTargetSetTransform(AzDrawTargetRef aDrawTarget,
                          AzMatrix *aTransform) {
@@ -466,6 +988,7 @@ AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle) {
     options->mStyle = static_cast<gfx::FontStyle>(aStyle);
     options->mData = NULL;
     options->mDataSize = 0;
+    options->mFaceIndex = 0;
     return options;
     #else
     abort();
@@ -473,12 +996,14 @@ AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle) {
 }
 
 extern "C" AzFontOptions*
-AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize) {
+AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize,
+                           uint32_t aFaceIndex) {
     #ifdef MOZ_ENABLE_FREETYPE
     gfx::FontOptions *options = new gfx::FontOptions;
     options->mStyle = gfx::FONT_STYLE_NORMAL;
     options->mData = aFontData;
     options->mDataSize = aFontDataSize;
+    options->mFaceIndex = aFaceIndex;
     return options;
     #else
     abort();
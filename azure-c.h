@@ -9,6 +9,7 @@
 
 #include "SkNativeSharedGLContext.h"
 
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 
@@ -92,6 +93,14 @@ enum AzFontStyle
   AZ_FONT_STYLE_BOLD_ITALIC
 };
 
+enum AzFontHinting
+{
+  AZ_FONT_HINTING_NONE,
+  AZ_FONT_HINTING_LIGHT,
+  AZ_FONT_HINTING_NORMAL,
+  AZ_FONT_HINTING_FULL
+};
+
 enum AzCompositionOp {
   AZ_OP_OVER,
   AZ_OP_ADD,
@@ -146,7 +155,8 @@ enum AzSnapping {
 
 enum AzFilter {
   AZ_FILTER_LINEAR,
-  AZ_FILTER_POINT
+  AZ_FILTER_POINT,
+  AZ_FILTER_GOOD
 };
 
 enum AzPatternType {
@@ -276,6 +286,12 @@ typedef struct _AzGlyphBuffer {
   uint32_t mNumGlyphs;
 } AzGlyphBuffer;
 
+typedef struct _AzFontMetrics {
+  AzFloat mAscent;
+  AzFloat mDescent;
+  AzFloat mLineGap;
+} AzFontMetrics;
+
 typedef struct _AzNativeFont {
   enum AzNativeFontType mType;
   void *mFont;
@@ -292,12 +308,19 @@ typedef void* AzPatternRef;
 typedef void* AzColorPatternRef;
 typedef void* AzScaledFontRef;
 typedef void* AzGlyphRenderingOptionsRef;
+typedef void (*AzFontFileDataOutput)(const uint8_t *aData, uint32_t aLength, uint32_t aIndex,
+                                     AzFloat aGlyphSize, void *aBaton);
+typedef void (*AzRecordingDataOutput)(const uint8_t *aData, size_t aLength, void *aBaton);
+// Fills aBuffer with up to aLength bytes of a recording and returns how many
+// it wrote, or 0 at the end of the recording.
+typedef size_t (*AzRecordingDataInput)(uint8_t *aBuffer, size_t aLength, void *aBaton);
 typedef void* AzSourceSurfaceRef;
 typedef void* AzDrawSurfaceOptionsRef;
 typedef void* AzDataSourceSurfaceRef;
 typedef void* AzGLContextMetadataRef;
 typedef void* AzPathBuilderRef;
 typedef void* AzPathRef;
+typedef void* AzDrawEventRecorderRef;
 
 typedef GrGLSharedContext AzGLContext;
 typedef GrGLNativeContext* AzGLNativeContextRef;
@@ -322,6 +345,8 @@ AzDrawTargetRef AzCreateDrawTargetForData(AzBackendType aBackend,
                                           AzIntSize *aSize,
                                           int32_t aStride,
                                           AzSurfaceFormat aFormat);
+AzDrawTargetRef AzCreateRecordingDrawTarget(AzDrawEventRecorderRef aRecorder,
+                                            AzDrawTargetRef aDrawTarget);
 AzDrawTargetRef AzCreateSkiaDrawTragetForFBO(AzSkiaSharedGLContextRef aGLContext,
                                              AzIntSize *aSize,
                                              AzSurfaceFormat aFormat);
@@ -360,6 +385,11 @@ void AzDrawTargetDrawSurface(AzDrawTargetRef aDrawTarget,
                              AzRect *aSource,
                              AzDrawSurfaceOptionsRef aSurfOptions,
                              AzDrawOptions *aOptions);
+void AzDrawTargetMaskSurface(AzDrawTargetRef aDrawTarget,
+                             AzPatternRef aSource,
+                             AzSourceSurfaceRef aMask,
+                             AzPoint *aOffset,
+                             AzDrawOptions *aOptions);
 AzSourceSurfaceRef AzDrawTargetGetSnapshot(AzDrawTargetRef aDrawTarget);
 AzSourceSurfaceRef AzDrawTargetCreateSourceSurfaceFromData(AzDrawTargetRef aDrawTarget,
                                                            const unsigned char *aData,
@@ -370,18 +400,204 @@ void AzReleaseSourceSurface(AzSourceSurfaceRef aSurface);
 AzIntSize AzSourceSurfaceGetSize(AzSourceSurfaceRef aSurface);
 AzSurfaceFormat AzSourceSurfaceGetFormat(AzSourceSurfaceRef aSurface);
 AzDataSourceSurfaceRef AzSourceSurfaceGetDataSurface(AzSourceSurfaceRef aSurface);
+AzDataSourceSurfaceRef AzCreateDataSourceSurface(AzIntSize *aSize, AzSurfaceFormat aFormat);
 uint8_t *AzDataSourceSurfaceGetData(AzDataSourceSurfaceRef aSurface);
 int32_t AzDataSourceSurfaceGetStride(AzDataSourceSurfaceRef aSurface);
 
 
+/* Swizzle.h */
+
+enum AzPixelFormat {
+  AZ_PIXEL_FORMAT_B8G8R8A8,
+  AZ_PIXEL_FORMAT_B8G8R8X8,
+  AZ_PIXEL_FORMAT_R5G6B5,
+  AZ_PIXEL_FORMAT_A8,
+  AZ_PIXEL_FORMAT_R8G8B8A8,
+  AZ_PIXEL_FORMAT_R8G8B8X8,
+  AZ_PIXEL_FORMAT_B8G8R8A8_UNPREMULTIPLIED,
+  AZ_PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED
+};
+
+bool AzConvertPixels(const uint8_t *aSrc, int32_t aSrcStride, AzPixelFormat aSrcFormat,
+                     uint8_t *aDst, int32_t aDstStride, AzPixelFormat aDstFormat,
+                     AzIntSize *aSize);
+
+
+/* Scale.h */
+
+enum AzResizeMethod {
+  AZ_RESIZE_GOOD,
+  AZ_RESIZE_BETTER,
+  AZ_RESIZE_BEST,
+  AZ_RESIZE_BOX,
+  AZ_RESIZE_HAMMING1,
+  AZ_RESIZE_LANCZOS2,
+  AZ_RESIZE_LANCZOS3
+};
+
+bool AzScale(const uint8_t *aSrc, AzIntSize *aSrcSize, int32_t aSrcStride,
+             uint8_t *aDst, AzIntSize *aDstSize, int32_t aDstStride,
+             AzSurfaceFormat aFormat, AzResizeMethod aMethod);
+
+
 /* Factory.h */
 
+AzScaledFontRef AzCreateScaledFontForTrueTypeData(const uint8_t *aData, uint32_t aSize,
+                                                  uint32_t aFaceIndex, AzFloat aGlyphSize,
+                                                  AzFontType aType);
+void AzRetainScaledFont(AzScaledFontRef aFont);
 void AzReleaseScaledFont(AzScaledFontRef aFont);
+AzPathRef AzScaledFontGetPathForGlyphs(AzScaledFontRef aFont, AzGlyphBuffer *aGlyphBuffer,
+                                       AzDrawTargetRef aDrawTarget);
+bool AzScaledFontCopyGlyphsToBuilder(AzScaledFontRef aFont, AzGlyphBuffer *aGlyphBuffer,
+                                     AzPathBuilderRef aPathBuilder);
+AzFontMetrics AzScaledFontGetFontMetrics(AzScaledFontRef aFont);
+AzFloat AzScaledFontGetGlyphAdvance(AzScaledFontRef aFont, uint32_t aGlyph);
+AzRect AzScaledFontGetGlyphBounds(AzScaledFontRef aFont, uint32_t aGlyph);
+void AzScaledFontGetGlyphIndices(AzScaledFontRef aFont, const uint32_t *aCodepoints,
+                                 uint32_t aCount, uint32_t *aGlyphs);
+bool AzScaledFontGetFontFileData(AzScaledFontRef aFont, AzFontFileDataOutput aDataCallback,
+                                 void *aBaton);
+
+AzGlyphRenderingOptionsRef AzCreateSkiaGlyphRenderingOptions(AzFontHinting aHinting,
+                                                             bool aSubpixelPositioning);
+AzGlyphRenderingOptionsRef AzCreateCGGlyphRenderingOptions(bool aSubpixelPositioning);
+void AzReleaseGlyphRenderingOptions(AzGlyphRenderingOptionsRef aOptions);
+
+AzDrawEventRecorderRef AzCreateDrawEventRecorderForFile(const char *aFilename);
+AzDrawEventRecorderRef AzCreateDrawEventRecorderMemory();
+void AzDrawEventRecorderMemoryTakeData(AzDrawEventRecorderRef aRecorder,
+                                       AzRecordingDataOutput aDataCallback,
+                                       void *aBaton);
+void AzRetainDrawEventRecorder(AzDrawEventRecorderRef aRecorder);
+void AzReleaseDrawEventRecorder(AzDrawEventRecorderRef aRecorder);
+void AzSetGlobalEventRecorder(AzDrawEventRecorderRef aRecorder);
+
+/* DrawEventPlayer.h */
+
+typedef void* AzDrawEventPlayerRef;
+
+enum AzRecordingError {
+  AZ_RECORDING_ERROR_NONE,
+  AZ_RECORDING_ERROR_BAD_HEADER,
+  AZ_RECORDING_ERROR_UNSUPPORTED_VERSION,
+  AZ_RECORDING_ERROR_UNKNOWN_EVENT,
+  AZ_RECORDING_ERROR_TRUNCATED,
+  AZ_RECORDING_ERROR_INVALID_EVENT,
+  AZ_RECORDING_ERROR_UNKNOWN_REFERENCE
+};
+
+AzDrawEventPlayerRef AzCreateDrawEventPlayer(AzBackendType aBackend);
+void AzReleaseDrawEventPlayer(AzDrawEventPlayerRef aPlayer);
+bool AzDrawEventPlayerPlay(AzDrawEventPlayerRef aPlayer,
+                           const uint8_t *aData,
+                           size_t aDataLen);
+bool AzDrawEventPlayerPlayInput(AzDrawEventPlayerRef aPlayer,
+                                AzRecordingDataInput aDataCallback,
+                                void *aBaton);
+AzRecordingError AzDrawEventPlayerGetError(AzDrawEventPlayerRef aPlayer);
+size_t AzDrawEventPlayerGetDrawTargetCount(AzDrawEventPlayerRef aPlayer);
+AzDrawTargetRef AzDrawEventPlayerGetDrawTarget(AzDrawEventPlayerRef aPlayer,
+                                               size_t aIndex);
+
+enum AzEventType {
+  AZ_EVENT_DRAWTARGETCREATION,
+  AZ_EVENT_DRAWTARGETDESTRUCTION,
+  AZ_EVENT_FILLRECT,
+  AZ_EVENT_STROKERECT,
+  AZ_EVENT_STROKELINE,
+  AZ_EVENT_CLEARRECT,
+  AZ_EVENT_COPYSURFACE,
+  AZ_EVENT_SETTRANSFORM,
+  AZ_EVENT_PUSHCLIP,
+  AZ_EVENT_PUSHCLIPRECT,
+  AZ_EVENT_POPCLIP,
+  AZ_EVENT_FILL,
+  AZ_EVENT_FILLGLYPHS,
+  AZ_EVENT_MASK,
+  AZ_EVENT_STROKE,
+  AZ_EVENT_DRAWSURFACE,
+  AZ_EVENT_DRAWSURFACEWITHSHADOW,
+  AZ_EVENT_PATHCREATION,
+  AZ_EVENT_PATHDESTRUCTION,
+  AZ_EVENT_SOURCESURFACECREATION,
+  AZ_EVENT_SOURCESURFACEDESTRUCTION,
+  AZ_EVENT_GRADIENTSTOPSCREATION,
+  AZ_EVENT_GRADIENTSTOPSDESTRUCTION,
+  AZ_EVENT_SNAPSHOT,
+  AZ_EVENT_SCALEDFONTCREATION,
+  AZ_EVENT_SCALEDFONTDESTRUCTION
+};
+
+typedef struct _AzRecordedEventDescription {
+  int32_t mType;
+  int32_t mPatternType;
+  int32_t mMaskPatternType;
+  int32_t mFormat;
+  int32_t mBackendType;
+  uint32_t mCount;
+  uint64_t mObject;
+  uint64_t mDestinedDT;
+  uint64_t mReference;
+  AzRect mRect;
+  AzRect mSourceRect;
+  AzPoint mPoint1;
+  AzPoint mPoint2;
+  AzMatrix mTransform;
+  AzColor mColor;
+  AzColor mMaskColor;
+  AzIntSize mSize;
+  AzFloat mValue;
+} AzRecordedEventDescription;
+
+typedef void* AzRecordedEventReaderRef;
+
+AzRecordedEventReaderRef AzCreateRecordedEventReader(const uint8_t *aData,
+                                                     size_t aDataLen);
+void AzReleaseRecordedEventReader(AzRecordedEventReaderRef aReader);
+bool AzRecordedEventReaderNext(AzRecordedEventReaderRef aReader,
+                               AzRecordedEventDescription *aDescription);
+bool AzRecordedEventReaderHasFailed(AzRecordedEventReaderRef aReader);
+AzRecordingError AzRecordedEventReaderGetError(AzRecordedEventReaderRef aReader);
+const char *AzRecordedEventReaderGetEventInfo(AzRecordedEventReaderRef aReader);
+bool AzDrawEventPlayerPlayCurrentEvent(AzDrawEventPlayerRef aPlayer,
+                                       AzRecordedEventReaderRef aReader);
+
+/* Blur.h */
+
+typedef void* AzAlphaBoxBlurRef;
+
+AzAlphaBoxBlurRef AzCreateAlphaBoxBlur(AzRect *aRect,
+                                       AzIntSize *aSpreadRadius,
+                                       AzIntSize *aBlurRadius,
+                                       AzRect *aDirtyRect,
+                                       AzRect *aSkipRect);
+void AzReleaseAlphaBoxBlur(AzAlphaBoxBlurRef aBlur);
+uint8_t *AzAlphaBoxBlurGetData(AzAlphaBoxBlurRef aBlur);
+AzIntSize AzAlphaBoxBlurGetSize(AzAlphaBoxBlurRef aBlur);
+int32_t AzAlphaBoxBlurGetStride(AzAlphaBoxBlurRef aBlur);
+AzIntRect AzAlphaBoxBlurGetRect(AzAlphaBoxBlurRef aBlur);
+void AzAlphaBoxBlurBlur(AzAlphaBoxBlurRef aBlur);
+AzIntSize AzAlphaBoxBlurCalculateBlurRadius(AzPoint *aStandardDeviation);
+
+/* ImageScaling.h */
+
+typedef void* AzImageHalfScalerRef;
+
+AzImageHalfScalerRef AzCreateImageHalfScaler(const uint8_t *aData,
+                                             int32_t aStride,
+                                             AzIntSize *aSize);
+void AzReleaseImageHalfScaler(AzImageHalfScalerRef aScaler);
+void AzImageHalfScalerScaleForSize(AzImageHalfScalerRef aScaler, AzIntSize *aSize);
+uint8_t *AzImageHalfScalerGetScaledData(AzImageHalfScalerRef aScaler);
+AzIntSize AzImageHalfScalerGetSize(AzImageHalfScalerRef aScaler);
+uint32_t AzImageHalfScalerGetStride(AzImageHalfScalerRef aScaler);
 
 /* Helpers */
 typedef void AzFontOptions;
 AzFontOptions* AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle);
-AzFontOptions* AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize);
+AzFontOptions* AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize,
+                                          uint32_t aFaceIndex);
 void AzDestroyFontOptions(AzFontOptions* aOptions);
 
 AzGLContext AzSkiaGetCurrentGLContext();
@@ -49,6 +49,8 @@ pub static AZ_FONT_SKIA: u32 = 3_u32;
 pub static AZ_FONT_CAIRO: u32 = 4_u32;
 pub static AZ_FONT_COREGRAPHICS: u32 = 5_u32;
 
+pub type AzFontType = enum_AzFontType;
+
 pub type enum_AzNativeSurfaceType = c_uint;
 pub static AZ_NATIVE_SURFACE_D3D10_TEXTURE: u32 = 0_u32;
 pub static AZ_NATIVE_SURFACE_CAIRO_SURFACE: u32 = 1_u32;
@@ -67,6 +69,16 @@ pub static AZ_FONT_STYLE_ITALIC: u32 = 1_u32;
 pub static AZ_FONT_STYLE_BOLD: u32 = 2_u32;
 pub static AZ_FONT_STYLE_BOLD_ITALIC: u32 = 3_u32;
 
+pub type AzFontStyle = enum_AzFontStyle;
+
+pub type enum_AzFontHinting = c_uint;
+pub static AZ_FONT_HINTING_NONE: u32 = 0_u32;
+pub static AZ_FONT_HINTING_LIGHT: u32 = 1_u32;
+pub static AZ_FONT_HINTING_NORMAL: u32 = 2_u32;
+pub static AZ_FONT_HINTING_FULL: u32 = 3_u32;
+
+pub type AzFontHinting = enum_AzFontHinting;
+
 pub type enum_AzCompositionOp = c_uint;
 pub static AZ_OP_OVER: u32 = 0_u32;
 pub static AZ_OP_ADD: u32 = 1_u32;
@@ -117,6 +129,7 @@ pub static AZ_SNAP_ALIGNED: u32 = 1_u32;
 pub type enum_AzFilter = c_uint;
 pub static AZ_FILTER_LINEAR: u32 = 0_u32;
 pub static AZ_FILTER_POINT: u32 = 1_u32;
+pub static AZ_FILTER_GOOD: u32 = 2_u32;
 
 pub type AzFilter = enum_AzFilter;
 
@@ -147,6 +160,70 @@ pub static AZ_eSideRight: u32 = 1_u32;
 pub static AZ_eSideBottom: u32 = 2_u32;
 pub static AZ_eSideLeft: u32 = 3_u32;
 
+pub type enum_AzPixelFormat = c_uint;
+pub static AZ_PIXEL_FORMAT_B8G8R8A8: u32 = 0_u32;
+pub static AZ_PIXEL_FORMAT_B8G8R8X8: u32 = 1_u32;
+pub static AZ_PIXEL_FORMAT_R5G6B5: u32 = 2_u32;
+pub static AZ_PIXEL_FORMAT_A8: u32 = 3_u32;
+pub static AZ_PIXEL_FORMAT_R8G8B8A8: u32 = 4_u32;
+pub static AZ_PIXEL_FORMAT_R8G8B8X8: u32 = 5_u32;
+pub static AZ_PIXEL_FORMAT_B8G8R8A8_UNPREMULTIPLIED: u32 = 6_u32;
+pub static AZ_PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED: u32 = 7_u32;
+
+pub type AzPixelFormat = enum_AzPixelFormat;
+
+pub type enum_AzResizeMethod = c_uint;
+pub static AZ_RESIZE_GOOD: u32 = 0_u32;
+pub static AZ_RESIZE_BETTER: u32 = 1_u32;
+pub static AZ_RESIZE_BEST: u32 = 2_u32;
+pub static AZ_RESIZE_BOX: u32 = 3_u32;
+pub static AZ_RESIZE_HAMMING1: u32 = 4_u32;
+pub static AZ_RESIZE_LANCZOS2: u32 = 5_u32;
+pub static AZ_RESIZE_LANCZOS3: u32 = 6_u32;
+
+pub type AzResizeMethod = enum_AzResizeMethod;
+
+pub type enum_AzEventType = c_uint;
+pub static AZ_EVENT_DRAWTARGETCREATION: u32 = 0_u32;
+pub static AZ_EVENT_DRAWTARGETDESTRUCTION: u32 = 1_u32;
+pub static AZ_EVENT_FILLRECT: u32 = 2_u32;
+pub static AZ_EVENT_STROKERECT: u32 = 3_u32;
+pub static AZ_EVENT_STROKELINE: u32 = 4_u32;
+pub static AZ_EVENT_CLEARRECT: u32 = 5_u32;
+pub static AZ_EVENT_COPYSURFACE: u32 = 6_u32;
+pub static AZ_EVENT_SETTRANSFORM: u32 = 7_u32;
+pub static AZ_EVENT_PUSHCLIP: u32 = 8_u32;
+pub static AZ_EVENT_PUSHCLIPRECT: u32 = 9_u32;
+pub static AZ_EVENT_POPCLIP: u32 = 10_u32;
+pub static AZ_EVENT_FILL: u32 = 11_u32;
+pub static AZ_EVENT_FILLGLYPHS: u32 = 12_u32;
+pub static AZ_EVENT_MASK: u32 = 13_u32;
+pub static AZ_EVENT_STROKE: u32 = 14_u32;
+pub static AZ_EVENT_DRAWSURFACE: u32 = 15_u32;
+pub static AZ_EVENT_DRAWSURFACEWITHSHADOW: u32 = 16_u32;
+pub static AZ_EVENT_PATHCREATION: u32 = 17_u32;
+pub static AZ_EVENT_PATHDESTRUCTION: u32 = 18_u32;
+pub static AZ_EVENT_SOURCESURFACECREATION: u32 = 19_u32;
+pub static AZ_EVENT_SOURCESURFACEDESTRUCTION: u32 = 20_u32;
+pub static AZ_EVENT_GRADIENTSTOPSCREATION: u32 = 21_u32;
+pub static AZ_EVENT_GRADIENTSTOPSDESTRUCTION: u32 = 22_u32;
+pub static AZ_EVENT_SNAPSHOT: u32 = 23_u32;
+pub static AZ_EVENT_SCALEDFONTCREATION: u32 = 24_u32;
+pub static AZ_EVENT_SCALEDFONTDESTRUCTION: u32 = 25_u32;
+
+pub type AzEventType = enum_AzEventType;
+
+pub type enum_AzRecordingError = c_uint;
+pub static AZ_RECORDING_ERROR_NONE: u32 = 0_u32;
+pub static AZ_RECORDING_ERROR_BAD_HEADER: u32 = 1_u32;
+pub static AZ_RECORDING_ERROR_UNSUPPORTED_VERSION: u32 = 2_u32;
+pub static AZ_RECORDING_ERROR_UNKNOWN_EVENT: u32 = 3_u32;
+pub static AZ_RECORDING_ERROR_TRUNCATED: u32 = 4_u32;
+pub static AZ_RECORDING_ERROR_INVALID_EVENT: u32 = 5_u32;
+pub static AZ_RECORDING_ERROR_UNKNOWN_REFERENCE: u32 = 6_u32;
+
+pub type AzRecordingError = enum_AzRecordingError;
+
 pub struct struct__AzColor {
     pub r: AzFloat,
     pub g: AzFloat,
@@ -258,6 +335,14 @@ pub struct struct__AzGlyphBuffer {
 
 pub type AzGlyphBuffer = struct__AzGlyphBuffer;
 
+pub struct struct__AzFontMetrics {
+    pub mAscent: AzFloat,
+    pub mDescent: AzFloat,
+    pub mLineGap: AzFloat,
+}
+
+pub type AzFontMetrics = struct__AzFontMetrics;
+
 pub struct struct__AzNativeFont {
     pub mType: enum_AzNativeFontType,
     pub mFont: *mut c_void,
@@ -285,6 +370,12 @@ pub type AzScaledFontRef = *mut c_void;
 
 pub type AzGlyphRenderingOptionsRef = *mut c_void;
 
+pub type AzFontFileDataOutput = extern "C" fn(aData: *const u8, aLength: u32, aIndex: u32, aGlyphSize: AzFloat, aBaton: *mut c_void);
+
+pub type AzRecordingDataOutput = extern "C" fn(aData: *const u8, aLength: size_t, aBaton: *mut c_void);
+
+pub type AzRecordingDataInput = extern "C" fn(aBuffer: *mut u8, aLength: size_t, aBaton: *mut c_void) -> size_t;
+
 pub type AzSourceSurfaceRef = *mut c_void;
 
 pub type AzDataSourceSurfaceRef = *mut c_void;
@@ -303,6 +394,39 @@ pub type AzPathRef = *mut c_void;
 
 pub type AzPathBuilderRef = *mut c_void;
 
+pub type AzDrawEventRecorderRef = *mut c_void;
+
+pub type AzDrawEventPlayerRef = *mut c_void;
+
+pub struct struct__AzRecordedEventDescription {
+    pub mType: int32_t,
+    pub mPatternType: int32_t,
+    pub mMaskPatternType: int32_t,
+    pub mFormat: int32_t,
+    pub mBackendType: int32_t,
+    pub mCount: uint32_t,
+    pub mObject: uint64_t,
+    pub mDestinedDT: uint64_t,
+    pub mReference: uint64_t,
+    pub mRect: AzRect,
+    pub mSourceRect: AzRect,
+    pub mPoint1: AzPoint,
+    pub mPoint2: AzPoint,
+    pub mTransform: AzMatrix,
+    pub mColor: AzColor,
+    pub mMaskColor: AzColor,
+    pub mSize: AzIntSize,
+    pub mValue: AzFloat,
+}
+
+pub type AzRecordedEventDescription = struct__AzRecordedEventDescription;
+
+pub type AzRecordedEventReaderRef = *mut c_void;
+
+pub type AzAlphaBoxBlurRef = *mut c_void;
+
+pub type AzImageHalfScalerRef = *mut c_void;
+
 #[link(name = "azure")]
 extern {
 
@@ -333,6 +457,8 @@ pub fn AzCreateDrawTarget(aBackend: AzBackendType, aSize: *mut AzIntSize, aForma
 
 pub fn AzCreateDrawTargetForData(aBackend: AzBackendType, aData: *mut c_uchar, aSize: *mut AzIntSize, aStride: i32, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
 
+pub fn AzCreateRecordingDrawTarget(aRecorder: AzDrawEventRecorderRef, aDrawTarget: AzDrawTargetRef) -> AzDrawTargetRef;
+
 pub fn AzCreateSkiaDrawTargetForFBO(aGLContext: AzSkiaSharedGLContextRef, aSize: *mut AzIntSize, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
 
 pub fn AzRetainDrawTarget(aTarget: AzDrawTargetRef);
@@ -364,6 +490,8 @@ pub fn AzDrawTargetFillGlyphs(aDrawTarget: AzDrawTargetRef, aFont: AzScaledFontR
 
 pub fn AzDrawTargetDrawSurface(aDrawTarget: AzDrawTargetRef, aSurface: AzSourceSurfaceRef, aDest: *mut AzRect, aSource: *mut AzRect, aSurfOptions: AzDrawSurfaceOptionsRef, aOptions: *mut AzDrawOptions);
 
+pub fn AzDrawTargetMaskSurface(aDrawTarget: AzDrawTargetRef, aSource: AzPatternRef, aMask: AzSourceSurfaceRef, aOffset: *mut AzPoint, aOptions: *mut AzDrawOptions);
+
 pub fn AzDrawTargetGetSnapshot(aDrawTarget: AzDrawTargetRef) -> AzSourceSurfaceRef;
 
 pub fn AzDrawTargetCreateSourceSurfaceFromData(aDrawTarget: AzDrawTargetRef, aData: *const u8, aSize: *mut AzIntSize, aStride: i32, aFormat: AzSurfaceFormat) -> AzSourceSurfaceRef;
@@ -376,17 +504,115 @@ pub fn AzSourceSurfaceGetFormat(aSurface: AzSourceSurfaceRef) -> AzSurfaceFormat
 
 pub fn AzSourceSurfaceGetDataSurface(aSurface: AzSourceSurfaceRef) -> AzDataSourceSurfaceRef;
 
+pub fn AzCreateDataSourceSurface(aSize: *mut AzIntSize, aFormat: AzSurfaceFormat) -> AzDataSourceSurfaceRef;
+
 pub fn AzDataSourceSurfaceGetData(aSurface: AzDataSourceSurfaceRef) -> *mut u8;
 
 pub fn AzDataSourceSurfaceGetStride(aSurface: AzDataSourceSurfaceRef) -> i32;
 
+pub fn AzConvertPixels(aSrc: *const u8, aSrcStride: i32, aSrcFormat: AzPixelFormat, aDst: *mut u8, aDstStride: i32, aDstFormat: AzPixelFormat, aSize: *mut AzIntSize) -> bool;
+
+pub fn AzScale(aSrc: *const u8, aSrcSize: *mut AzIntSize, aSrcStride: i32, aDst: *mut u8, aDstSize: *mut AzIntSize, aDstStride: i32, aFormat: AzSurfaceFormat, aMethod: AzResizeMethod) -> bool;
+
 pub fn AzCreateScaledFontForNativeFont(aNativeFont: *mut AzNativeFont, aSize: AzFloat) -> AzScaledFontRef;
 
+pub fn AzCreateScaledFontForTrueTypeData(aData: *const u8, aSize: u32, aFaceIndex: u32, aGlyphSize: AzFloat, aType: AzFontType) -> AzScaledFontRef;
+
+pub fn AzRetainScaledFont(aFont: AzScaledFontRef);
+
 pub fn AzReleaseScaledFont(aFont: AzScaledFontRef);
 
+pub fn AzScaledFontGetPathForGlyphs(aFont: AzScaledFontRef, aGlyphBuffer: *mut AzGlyphBuffer, aDrawTarget: AzDrawTargetRef) -> AzPathRef;
+
+pub fn AzScaledFontCopyGlyphsToBuilder(aFont: AzScaledFontRef, aGlyphBuffer: *mut AzGlyphBuffer, aPathBuilder: AzPathBuilderRef) -> bool;
+
+pub fn AzCreateSkiaGlyphRenderingOptions(aHinting: AzFontHinting, aSubpixelPositioning: bool) -> AzGlyphRenderingOptionsRef;
+
+pub fn AzCreateCGGlyphRenderingOptions(aSubpixelPositioning: bool) -> AzGlyphRenderingOptionsRef;
+
+pub fn AzScaledFontGetFontMetrics(aFont: AzScaledFontRef) -> AzFontMetrics;
+
+pub fn AzScaledFontGetGlyphAdvance(aFont: AzScaledFontRef, aGlyph: u32) -> AzFloat;
+
+pub fn AzScaledFontGetGlyphBounds(aFont: AzScaledFontRef, aGlyph: u32) -> AzRect;
+
+pub fn AzScaledFontGetGlyphIndices(aFont: AzScaledFontRef, aCodepoints: *const u32, aCount: u32, aGlyphs: *mut u32);
+
+pub fn AzScaledFontGetFontFileData(aFont: AzScaledFontRef, aDataCallback: AzFontFileDataOutput, aBaton: *mut c_void) -> bool;
+
+pub fn AzReleaseGlyphRenderingOptions(aOptions: AzGlyphRenderingOptionsRef);
+
+pub fn AzCreateDrawEventRecorderForFile(aFilename: *const c_char) -> AzDrawEventRecorderRef;
+
+pub fn AzCreateDrawEventRecorderMemory() -> AzDrawEventRecorderRef;
+
+pub fn AzDrawEventRecorderMemoryTakeData(aRecorder: AzDrawEventRecorderRef, aDataCallback: AzRecordingDataOutput, aBaton: *mut c_void);
+
+pub fn AzRetainDrawEventRecorder(aRecorder: AzDrawEventRecorderRef);
+
+pub fn AzReleaseDrawEventRecorder(aRecorder: AzDrawEventRecorderRef);
+
+pub fn AzSetGlobalEventRecorder(aRecorder: AzDrawEventRecorderRef);
+
+pub fn AzCreateDrawEventPlayer(aBackend: AzBackendType) -> AzDrawEventPlayerRef;
+
+pub fn AzReleaseDrawEventPlayer(aPlayer: AzDrawEventPlayerRef);
+
+pub fn AzDrawEventPlayerPlay(aPlayer: AzDrawEventPlayerRef, aData: *const u8, aDataLen: size_t) -> bool;
+
+pub fn AzDrawEventPlayerPlayInput(aPlayer: AzDrawEventPlayerRef, aDataCallback: AzRecordingDataInput, aBaton: *mut c_void) -> bool;
+
+pub fn AzDrawEventPlayerGetError(aPlayer: AzDrawEventPlayerRef) -> AzRecordingError;
+
+pub fn AzDrawEventPlayerGetDrawTargetCount(aPlayer: AzDrawEventPlayerRef) -> size_t;
+
+pub fn AzDrawEventPlayerGetDrawTarget(aPlayer: AzDrawEventPlayerRef, aIndex: size_t) -> AzDrawTargetRef;
+
+pub fn AzCreateRecordedEventReader(aData: *const u8, aDataLen: size_t) -> AzRecordedEventReaderRef;
+
+pub fn AzReleaseRecordedEventReader(aReader: AzRecordedEventReaderRef);
+
+pub fn AzRecordedEventReaderNext(aReader: AzRecordedEventReaderRef, aDescription: *mut AzRecordedEventDescription) -> bool;
+
+pub fn AzRecordedEventReaderHasFailed(aReader: AzRecordedEventReaderRef) -> bool;
+
+pub fn AzRecordedEventReaderGetError(aReader: AzRecordedEventReaderRef) -> AzRecordingError;
+
+pub fn AzRecordedEventReaderGetEventInfo(aReader: AzRecordedEventReaderRef) -> *const c_char;
+
+pub fn AzDrawEventPlayerPlayCurrentEvent(aPlayer: AzDrawEventPlayerRef, aReader: AzRecordedEventReaderRef) -> bool;
+
+pub fn AzCreateAlphaBoxBlur(aRect: *mut AzRect, aSpreadRadius: *mut AzIntSize, aBlurRadius: *mut AzIntSize, aDirtyRect: *mut AzRect, aSkipRect: *mut AzRect) -> AzAlphaBoxBlurRef;
+
+pub fn AzReleaseAlphaBoxBlur(aBlur: AzAlphaBoxBlurRef);
+
+pub fn AzAlphaBoxBlurGetData(aBlur: AzAlphaBoxBlurRef) -> *mut u8;
+
+pub fn AzAlphaBoxBlurGetSize(aBlur: AzAlphaBoxBlurRef) -> AzIntSize;
+
+pub fn AzAlphaBoxBlurGetStride(aBlur: AzAlphaBoxBlurRef) -> i32;
+
+pub fn AzAlphaBoxBlurGetRect(aBlur: AzAlphaBoxBlurRef) -> AzIntRect;
+
+pub fn AzAlphaBoxBlurBlur(aBlur: AzAlphaBoxBlurRef);
+
+pub fn AzAlphaBoxBlurCalculateBlurRadius(aStandardDeviation: *mut AzPoint) -> AzIntSize;
+
+pub fn AzCreateImageHalfScaler(aData: *const u8, aStride: i32, aSize: *mut AzIntSize) -> AzImageHalfScalerRef;
+
+pub fn AzReleaseImageHalfScaler(aScaler: AzImageHalfScalerRef);
+
+pub fn AzImageHalfScalerScaleForSize(aScaler: AzImageHalfScalerRef, aSize: *mut AzIntSize);
+
+pub fn AzImageHalfScalerGetScaledData(aScaler: AzImageHalfScalerRef) -> *mut u8;
+
+pub fn AzImageHalfScalerGetSize(aScaler: AzImageHalfScalerRef) -> AzIntSize;
+
+pub fn AzImageHalfScalerGetStride(aScaler: AzImageHalfScalerRef) -> u32;
+
 pub fn AzDrawTargetSetTransform(aDrawTarget: AzDrawTargetRef, aTransform: *mut AzMatrix);
 
-pub fn AzCreateFontOptionsForData(aFontData: *const u8, aFontDataSize: u32) -> *mut AzFontOptions;
+pub fn AzCreateFontOptionsForData(aFontData: *const u8, aFontDataSize: u32, aFaceIndex: u32) -> *mut AzFontOptions;
 
 pub fn AzCreateFontOptionsForName(aName: *const c_char, aStyle: enum_AzFontStyle) -> *mut AzFontOptions;
 
@@ -8,8 +8,7 @@ use azure::{AZ_CAP_BUTT, AZ_JOIN_MITER_OR_BEVEL};
 use azure::{AzPoint, AzRect, AzFloat, AzIntSize, AzColor, AzColorPatternRef};
 use azure::{AzStrokeOptions, AzDrawOptions, AzSurfaceFormat, AzFilter, AzDrawSurfaceOptions};
 use azure::{AzBackendType, AzDrawTargetRef, AzSourceSurfaceRef, AzDataSourceSurfaceRef};
-use azure::{AzScaledFontRef, AzGlyphRenderingOptionsRef};
-use azure::{struct__AzColor, struct__AzGlyphBuffer};
+use azure::{struct__AzColor};
 use azure::{struct__AzDrawOptions, struct__AzDrawSurfaceOptions, struct__AzIntSize};
 use azure::{struct__AzPoint, struct__AzRect, struct__AzStrokeOptions};
 use azure::{AzGLContext, AzSkiaSharedGLContextRef};
@@ -29,8 +28,17 @@ use azure::{AzSkiaSharedGLContextMakeCurrent, AzSkiaSharedGLContextStealSurface}
 use azure::{AzSkiaSharedGLContextFlush, AzSkiaGrGLSharedSurfaceRef};
 use azure::{AzCreatePathBuilder, AzPathBuilderRef, AzPathBuilderMoveTo, AzPathBuilderLineTo, AzPathBuilderFinish, AzReleasePathBuilder};
 use azure::{AzDrawTargetFill, AzPathRef, AzReleasePath, AzDrawTargetPushClip, AzDrawTargetPopClip};
+use azure::{AzPixelFormat, AzConvertPixels, AzCreateDataSourceSurface};
+use azure::{AzResizeMethod, AzScale};
+use azure::{AzCreateImageHalfScaler, AzReleaseImageHalfScaler, AzImageHalfScalerScaleForSize};
+use azure::{AzImageHalfScalerGetScaledData, AzImageHalfScalerGetSize, AzImageHalfScalerGetStride};
+use azure::{AzDrawEventRecorderRef, AzCreateDrawEventRecorderForFile, AzCreateRecordingDrawTarget};
+use azure::{AzRetainDrawEventRecorder, AzReleaseDrawEventRecorder, AzCreateDrawEventRecorderMemory};
+use azure::AzDrawEventRecorderMemoryTakeData;
+use azure::{AzSetGlobalEventRecorder, AzDrawTargetMaskSurface};
 
 use sync::Arc;
+use blur::AlphaBoxBlur;
 use geom::matrix2d::Matrix2D;
 use geom::point::Point2D;
 use geom::rect::Rect;
@@ -38,6 +46,12 @@ use geom::size::Size2D;
 use layers::platform::surface::{NativeGraphicsMetadata, NativePaintingGraphicsContext};
 use libc::types::common::c99::{uint8_t, uint16_t};
 use libc::size_t;
+use png;
+use recording::{DrawEventPlayer, RecordingError};
+use scaled_font::{Glyph, GlyphRenderingOptions, ScaledFont, with_azure_glyph_buffer};
+use std::c_str::ToCStr;
+use std::f32::consts::{PI, FRAC_PI_2};
+use std::io::{IoResult, Writer};
 use std::mem;
 use std::ptr;
 use std::slice;
@@ -237,6 +251,7 @@ impl DrawOptions {
 }
 
 
+#[deriving(Clone, PartialEq)]
 pub enum SurfaceFormat {
     B8G8R8A8,
     B8G8R8X8,
@@ -258,11 +273,135 @@ impl SurfaceFormat {
             _ => fail!("SurfaceFormat::new(): unknown Azure surface format")
         }
     }
+
+    pub fn bytes_per_pixel(self) -> i32 {
+        self.as_pixel_format().bytes_per_pixel()
+    }
+
+    pub fn as_pixel_format(self) -> PixelFormat {
+        match self {
+            B8G8R8A8 => PremultipliedBGRA,
+            B8G8R8X8 => OpaqueBGRX,
+            R5G6B5   => PackedRGB565,
+            A8       => AlphaMask,
+        }
+    }
+}
+
+/// The pixel layouts that `convert_pixels` can convert between. The first four are Azure's own
+/// `SurfaceFormat`s, which are native-endian and premultiplied; the rest are the byte-ordered
+/// buffers that image decoders and GL want, with either premultiplied or straight alpha.
+#[deriving(Clone, PartialEq)]
+pub enum PixelFormat {
+    PremultipliedBGRA,
+    OpaqueBGRX,
+    PackedRGB565,
+    AlphaMask,
+    PremultipliedRGBA,
+    OpaqueRGBX,
+    StraightBGRA,
+    StraightRGBA,
+}
+
+impl PixelFormat {
+    fn as_azure_pixel_format(self) -> AzPixelFormat {
+        self as AzPixelFormat
+    }
+
+    pub fn bytes_per_pixel(self) -> i32 {
+        match self {
+            AlphaMask    => 1,
+            PackedRGB565 => 2,
+            _            => 4,
+        }
+    }
+}
+
+fn assert_pixel_buffer_size(len: uint, stride: i32, format: PixelFormat, size: Size2D<i32>) {
+    assert!(size.width >= 0 && size.height >= 0);
+    assert!(stride >= size.width * format.bytes_per_pixel());
+    if size.height > 0 {
+        let needed = stride * (size.height - 1) + size.width * format.bytes_per_pixel();
+        assert!(len as i32 >= needed);
+    }
+}
+
+/// Converts `size` pixels from `src` to `dst`, premultiplying, unpremultiplying, swapping red and
+/// blue, and packing or unpacking R5G6B5 as the two formats require. Converting to a format
+/// without alpha composites premultiplied pixels over black; converting to `AlphaMask` keeps only
+/// the alpha channel. Uses SSE2 when the CPU supports it.
+pub fn convert_pixels(src: &[u8],
+                      src_stride: i32,
+                      src_format: PixelFormat,
+                      dst: &mut [u8],
+                      dst_stride: i32,
+                      dst_format: PixelFormat,
+                      size: Size2D<i32>) {
+    assert_pixel_buffer_size(src.len(), src_stride, src_format, size);
+    assert_pixel_buffer_size(dst.len(), dst_stride, dst_format, size);
+    unsafe {
+        if !AzConvertPixels(src.as_ptr(), src_stride, src_format.as_azure_pixel_format(),
+                            dst.as_mut_ptr(), dst_stride, dst_format.as_azure_pixel_format(),
+                            &mut size.as_azure_int_size()) {
+            fail!("convert_pixels(): conversion failed");
+        }
+    }
+}
+
+/// Like `convert_pixels`, but converts the pixels in place. Both formats must have the same
+/// number of bytes per pixel.
+pub fn convert_pixels_in_place(data: &mut [u8],
+                               stride: i32,
+                               src_format: PixelFormat,
+                               dst_format: PixelFormat,
+                               size: Size2D<i32>) {
+    assert!(src_format.bytes_per_pixel() == dst_format.bytes_per_pixel());
+    assert_pixel_buffer_size(data.len(), stride, src_format, size);
+    unsafe {
+        let ptr = data.as_mut_ptr();
+        if !AzConvertPixels(ptr as *const u8, stride, src_format.as_azure_pixel_format(),
+                            ptr, stride, dst_format.as_azure_pixel_format(),
+                            &mut size.as_azure_int_size()) {
+            fail!("convert_pixels_in_place(): conversion failed");
+        }
+    }
+}
+
+/// Premultiplies a B8G8R8A8 buffer with straight alpha in place.
+pub fn premultiply(data: &mut [u8], stride: i32, size: Size2D<i32>) {
+    convert_pixels_in_place(data, stride, StraightBGRA, PremultipliedBGRA, size)
+}
+
+/// Unpremultiplies a B8G8R8A8 buffer in place, leaving it with straight alpha.
+pub fn unpremultiply(data: &mut [u8], stride: i32, size: Size2D<i32>) {
+    convert_pixels_in_place(data, stride, PremultipliedBGRA, StraightBGRA, size)
+}
+
+/// The resampling filters `DataSourceSurface::resize` can use, from fastest to best looking.
+/// The quality tiers leave the choice of filter to Azure; the others name one.
+#[deriving(Clone, PartialEq)]
+pub enum ResizeMethod {
+    ResizeGood,
+    ResizeBetter,
+    ResizeBest,
+    ResizeBox,
+    ResizeHamming1,
+    ResizeLanczos2,
+    ResizeLanczos3,
+}
+
+impl ResizeMethod {
+    fn as_azure_resize_method(self) -> AzResizeMethod {
+        self as AzResizeMethod
+    }
 }
 
 pub enum Filter {
     Linear,
-    Point
+    Point,
+    /// Like `Linear`, except that draw targets in memory first resample the source with a
+    /// better filter when it is drawn at less than half its size, to avoid aliasing.
+    Good,
 }
 
 impl Filter {
@@ -291,6 +430,224 @@ impl DrawSurfaceOptions {
     }
 }
 
+/// The radii of the corners of a rounded rectangle, like the CSS `border-radius` property. Each
+/// corner is a quarter of an ellipse with the given horizontal and vertical radii.
+#[deriving(Clone, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: Size2D<AzFloat>,
+    pub top_right: Size2D<AzFloat>,
+    pub bottom_right: Size2D<AzFloat>,
+    pub bottom_left: Size2D<AzFloat>,
+}
+
+impl CornerRadii {
+    pub fn new(top_left: Size2D<AzFloat>,
+               top_right: Size2D<AzFloat>,
+               bottom_right: Size2D<AzFloat>,
+               bottom_left: Size2D<AzFloat>)
+               -> CornerRadii {
+        CornerRadii {
+            top_left: top_left,
+            top_right: top_right,
+            bottom_right: bottom_right,
+            bottom_left: bottom_left,
+        }
+    }
+
+    /// Returns radii which round every corner into a quarter circle of the given radius.
+    pub fn uniform(radius: AzFloat) -> CornerRadii {
+        let radius = Size2D(radius, radius);
+        CornerRadii::new(radius, radius, radius, radius)
+    }
+
+    /// Returns the radii of a rectangle with square corners.
+    pub fn zero() -> CornerRadii {
+        CornerRadii::uniform(0.0)
+    }
+
+    fn map(&self, f: |Size2D<AzFloat>| -> Size2D<AzFloat>) -> CornerRadii {
+        CornerRadii::new(f(self.top_left),
+                         f(self.top_right),
+                         f(self.bottom_right),
+                         f(self.bottom_left))
+    }
+
+    /// Grows every rounded corner by `amount`, or shrinks it for negative amounts, as CSS does
+    /// for the spread of a box shadow. Square corners stay square.
+    fn spread(&self, amount: AzFloat) -> CornerRadii {
+        self.map(|radius| {
+            if radius.width <= 0.0 || radius.height <= 0.0 {
+                Size2D(0.0, 0.0)
+            } else {
+                Size2D((radius.width + amount).max(0.0), (radius.height + amount).max(0.0))
+            }
+        })
+    }
+
+    /// Scales the radii down in proportion until adjacent corners no longer overlap along any
+    /// side of `rect`, as CSS does.
+    fn fit_to(&self, rect: &Rect<AzFloat>) -> CornerRadii {
+        let sides = [
+            (rect.size.width, self.top_left.width + self.top_right.width),
+            (rect.size.width, self.bottom_left.width + self.bottom_right.width),
+            (rect.size.height, self.top_left.height + self.bottom_left.height),
+            (rect.size.height, self.top_right.height + self.bottom_right.height),
+        ];
+        let mut scale: AzFloat = 1.0;
+        for &(length, radii) in sides.iter() {
+            if radii > 0.0 && length / radii < scale {
+                scale = length / radii;
+            }
+        }
+        if scale >= 1.0 {
+            return self.clone()
+        }
+        self.map(|radius| Size2D(radius.width * scale, radius.height * scale))
+    }
+}
+
+// The number of line segments which approximate each rounded corner of a clip.
+static CORNER_SEGMENTS: uint = 8;
+
+// The number of samples along each axis used for pixels near a rounded corner.
+static CORNER_SAMPLES: uint = 4;
+
+/// A rectangle with rounded corners, rasterized by hand into the buffers of box shadows.
+struct RoundedRect {
+    rect: Rect<AzFloat>,
+    radii: CornerRadii,
+}
+
+impl RoundedRect {
+    fn new(rect: Rect<AzFloat>, radii: &CornerRadii) -> RoundedRect {
+        RoundedRect {
+            radii: radii.fit_to(&rect),
+            rect: rect,
+        }
+    }
+
+    fn edges(&self) -> (AzFloat, AzFloat, AzFloat, AzFloat) {
+        let (left, top) = (self.rect.origin.x, self.rect.origin.y);
+        (left, top, left + self.rect.size.width, top + self.rect.size.height)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rect.size.width <= 0.0 || self.rect.size.height <= 0.0
+    }
+
+    /// Returns the largest rectangle inside this one which doesn't reach into any corner.
+    fn inner_rect(&self) -> Rect<AzFloat> {
+        let (left, top, right, bottom) = self.edges();
+        let radii = &self.radii;
+        let inner_left = left + radii.top_left.width.max(radii.bottom_left.width);
+        let inner_top = top + radii.top_left.height.max(radii.top_right.height);
+        let inner_right = right - radii.top_right.width.max(radii.bottom_right.width);
+        let inner_bottom = bottom - radii.bottom_left.height.max(radii.bottom_right.height);
+        Rect(Point2D(inner_left, inner_top),
+             Size2D((inner_right - inner_left).max(0.0), (inner_bottom - inner_top).max(0.0)))
+    }
+
+    fn contains(&self, x: AzFloat, y: AzFloat) -> bool {
+        let (left, top, right, bottom) = self.edges();
+        if x < left || x >= right || y < top || y >= bottom {
+            return false
+        }
+        let radii = &self.radii;
+        let (radius, center_x, center_y) =
+            if x < left + radii.top_left.width && y < top + radii.top_left.height {
+                (radii.top_left, left + radii.top_left.width, top + radii.top_left.height)
+            } else if x >= right - radii.top_right.width && y < top + radii.top_right.height {
+                (radii.top_right, right - radii.top_right.width, top + radii.top_right.height)
+            } else if x >= right - radii.bottom_right.width &&
+                    y >= bottom - radii.bottom_right.height {
+                (radii.bottom_right,
+                 right - radii.bottom_right.width,
+                 bottom - radii.bottom_right.height)
+            } else if x < left + radii.bottom_left.width &&
+                    y >= bottom - radii.bottom_left.height {
+                (radii.bottom_left,
+                 left + radii.bottom_left.width,
+                 bottom - radii.bottom_left.height)
+            } else {
+                return true
+            };
+        let dx = (x - center_x) / radius.width;
+        let dy = (y - center_y) / radius.height;
+        dx * dx + dy * dy <= 1.0
+    }
+
+    /// Returns how much of the pixel whose top left corner is at (`x`, `y`) is covered, from 0 to
+    /// 255. Pixels near a rounded corner are supersampled; elsewhere the coverage is exact.
+    fn coverage(&self, x: AzFloat, y: AzFloat) -> u8 {
+        let (left, top, right, bottom) = self.edges();
+        let covered_width = (x + 1.0).min(right) - x.max(left);
+        let covered_height = (y + 1.0).min(bottom) - y.max(top);
+        if covered_width <= 0.0 || covered_height <= 0.0 {
+            return 0
+        }
+
+        let radii = &self.radii;
+        let overlaps = |corner_left: AzFloat, corner_top: AzFloat, size: Size2D<AzFloat>| {
+            x < corner_left + size.width && x + 1.0 > corner_left &&
+                y < corner_top + size.height && y + 1.0 > corner_top
+        };
+        let near_corner =
+            overlaps(left, top, radii.top_left) ||
+            overlaps(right - radii.top_right.width, top, radii.top_right) ||
+            overlaps(right - radii.bottom_right.width,
+                     bottom - radii.bottom_right.height,
+                     radii.bottom_right) ||
+            overlaps(left, bottom - radii.bottom_left.height, radii.bottom_left);
+        if !near_corner {
+            return (covered_width * covered_height * 255.0 + 0.5) as u8
+        }
+
+        let mut inside = 0u;
+        for i in range(0, CORNER_SAMPLES) {
+            for j in range(0, CORNER_SAMPLES) {
+                let sample_x = x + (i as AzFloat + 0.5) / CORNER_SAMPLES as AzFloat;
+                let sample_y = y + (j as AzFloat + 0.5) / CORNER_SAMPLES as AzFloat;
+                if self.contains(sample_x, sample_y) {
+                    inside += 1;
+                }
+            }
+        }
+        let samples = CORNER_SAMPLES * CORNER_SAMPLES;
+        ((inside * 255 + samples / 2) / samples) as u8
+    }
+
+    /// Adds the outline to `builder`, with the corners approximated by line segments, going
+    /// clockwise on screen or, if `reverse` is set, anticlockwise.
+    fn add_to_path(&self, builder: &PathBuilder, reverse: bool) {
+        let (left, top, right, bottom) = self.edges();
+        let radii = &self.radii;
+        let corners = [
+            (left + radii.top_left.width, top + radii.top_left.height, radii.top_left, PI),
+            (right - radii.top_right.width, top + radii.top_right.height, radii.top_right,
+             PI + FRAC_PI_2),
+            (right - radii.bottom_right.width, bottom - radii.bottom_right.height,
+             radii.bottom_right, 0.0),
+            (left + radii.bottom_left.width, bottom - radii.bottom_left.height, radii.bottom_left,
+             FRAC_PI_2),
+        ];
+        let mut points = vec!();
+        for &(center_x, center_y, radius, start_angle) in corners.iter() {
+            for i in range(0, CORNER_SEGMENTS + 1) {
+                let angle = start_angle + FRAC_PI_2 * i as AzFloat / CORNER_SEGMENTS as AzFloat;
+                points.push(Point2D(center_x + radius.width * angle.cos(),
+                                    center_y + radius.height * angle.sin()));
+            }
+        }
+        if reverse {
+            points.reverse();
+        }
+        builder.move_to(*points.get(0));
+        for point in points.slice_from(1).iter() {
+            builder.line_to(*point);
+        }
+    }
+}
+
 
 #[deriving(Clone, PartialEq)]
 pub enum BackendType {
@@ -304,6 +661,19 @@ pub enum BackendType {
 }
 
 impl BackendType {
+    pub fn new(azure_backend_type: AzBackendType) -> BackendType {
+        match azure_backend_type {
+            0 => NoBackend,
+            1 => Direct2DBackend,
+            2 => CoreGraphicsBackend,
+            3 => CoreGraphicsAcceleratedBackend,
+            4 => CairoBackend,
+            5 => SkiaBackend,
+            6 => RecordingBackend,
+            _ => fail!("BackendType::new(): unknown Azure backend type")
+        }
+    }
+
     pub fn as_azure_backend_type(self) -> AzBackendType {
         match self {
             NoBackend                      => 0,
@@ -403,6 +773,35 @@ impl DrawTarget {
         }
     }
 
+    /// Creates a draw target that records every drawing command to `recorder` before passing it
+    /// on to `draw_target`.
+    ///
+    /// Recording isn't thread-safe: the recorder and every draw target recording to it have to
+    /// stay on one task. Fonts drawn to it can still be shared with other tasks. The recorder
+    /// keeps a reference to each of them. Whenever a new font is drawn, and when
+    /// `take_recorded_data` is called, it releases the fonts it holds the last reference to and
+    /// records their destruction.
+    pub fn new_recording(recorder: &DrawEventRecorder, draw_target: &DrawTarget) -> DrawTarget {
+        unsafe {
+            let azure_draw_target =
+                AzCreateRecordingDrawTarget(recorder.azure_draw_event_recorder,
+                                            draw_target.azure_draw_target);
+            if azure_draw_target == ptr::mut_null() { fail!("null azure draw target"); }
+            match draw_target.skia_context {
+                None => {}
+                Some(ctx_ref) => { AzRetainSkiaSharedGLContext(ctx_ref); }
+            }
+            DrawTarget {
+                azure_draw_target: azure_draw_target,
+                data: match draw_target.data {
+                    None => None,
+                    Some(ref arc) => Some(arc.clone())
+                },
+                skia_context: draw_target.skia_context
+            }
+        }
+    }
+
     pub fn clone(&self) -> DrawTarget {
         unsafe {
             AzRetainDrawTarget(self.azure_draw_target);
@@ -577,19 +976,46 @@ impl DrawTarget {
     }
 
     pub fn fill_glyphs(&self,
-                       azfontref: AzScaledFontRef,
-                       mut glyphbuf: struct__AzGlyphBuffer,
-                       azure_pattern: AzColorPatternRef,
-                       mut options: struct__AzDrawOptions,
-                       renderingOptions: AzGlyphRenderingOptionsRef) {
-        unsafe {
-            AzDrawTargetFillGlyphs(self.azure_draw_target,
-                    azfontref,
-                    &mut glyphbuf,
-                    azure_pattern,
-                    &mut options,
-                    renderingOptions);
+                       font: &ScaledFont,
+                       glyphs: &[Glyph],
+                       pattern: &ColorPattern,
+                       draw_options: &DrawOptions,
+                       rendering_options: Option<&GlyphRenderingOptions>) {
+        if glyphs.is_empty() {
+            return;
         }
+        let rendering_options = match rendering_options {
+            None => ptr::mut_null(),
+            Some(rendering_options) => rendering_options.get_ref(),
+        };
+        with_azure_glyph_buffer(glyphs, |glyph_buffer| {
+            unsafe {
+                AzDrawTargetFillGlyphs(self.azure_draw_target,
+                                       font.get_ref(),
+                                       glyph_buffer,
+                                       pattern.azure_color_pattern,
+                                       &mut draw_options.as_azure_draw_options(),
+                                       rendering_options);
+            }
+        })
+    }
+
+    /// Draws `text` with the pen starting at `origin` on the baseline. Characters map one to one
+    /// onto glyphs, which are laid out by their advances with no shaping, kerning or font
+    /// fallback, so this is only meant for simple labels in Latin text.
+    pub fn fill_text(&self,
+                     font: &ScaledFont,
+                     text: &str,
+                     origin: Point2D<AzFloat>,
+                     pattern: &ColorPattern,
+                     draw_options: &DrawOptions) {
+        let mut pen = origin;
+        let mut glyphs = Vec::new();
+        for &glyph_id in font.glyph_ids(text).iter() {
+            glyphs.push(Glyph::new(glyph_id, pen));
+            pen.x += font.glyph_advance(glyph_id);
+        }
+        self.fill_glyphs(font, glyphs.as_slice(), pattern, draw_options, None);
     }
 
     pub fn create_path_builder(&self) -> PathBuilder {
@@ -612,6 +1038,113 @@ impl DrawTarget {
         }
 
     }
+
+    /// Fills with `pattern`, using the alpha channel of `mask` as coverage. The mask's top left
+    /// corner is placed at `offset` in user space; beyond its edges, its edge pixels repeat.
+    pub fn mask_surface(&self,
+                        pattern: &ColorPattern,
+                        mask: SourceSurface,
+                        offset: Point2D<AzFloat>,
+                        draw_options: &DrawOptions) {
+        unsafe {
+            AzDrawTargetMaskSurface(self.azure_draw_target,
+                                    pattern.azure_color_pattern,
+                                    mask.azure_source_surface,
+                                    &mut offset.as_azure_point(),
+                                    &mut draw_options.as_azure_draw_options());
+        }
+    }
+
+    /// Draws the shadow of a box as the CSS `box-shadow` property does. The shadow has the
+    /// shape of the box `rect` with corners rounded by `radii`, moved by `offset`, grown by
+    /// `spread` (or shrunk, for negative values) and blurred by a Gaussian with standard
+    /// deviation `blur_sigma`. An outer shadow only shows outside the box. An `inset` shadow
+    /// only shows inside it, and is cast by the box's edges onto the shifted shape.
+    ///
+    /// The blur works at one pixel per unit of user space, so the shadow is only exact while
+    /// the transform is a translation by whole pixels.
+    pub fn draw_box_shadow(&self,
+                           rect: &Rect<AzFloat>,
+                           radii: &CornerRadii,
+                           color: Color,
+                           offset: Point2D<AzFloat>,
+                           blur_sigma: AzFloat,
+                           spread: AzFloat,
+                           inset: bool) {
+        let border_box = RoundedRect::new(*rect, radii);
+        if border_box.is_empty() {
+            return
+        }
+
+        let spread = if inset { -spread } else { spread };
+        let shadow_box = RoundedRect::new(
+            Rect(Point2D(rect.origin.x + offset.x - spread, rect.origin.y + offset.y - spread),
+                 Size2D((rect.size.width + 2.0 * spread).max(0.0),
+                        (rect.size.height + 2.0 * spread).max(0.0))),
+            &border_box.radii.spread(spread));
+        if !inset && shadow_box.is_empty() {
+            return
+        }
+
+        // An outer shadow is drawn into a buffer around the shadow, and is hidden inside the box.
+        // An inset one is drawn into a buffer around the box, and is clear well inside the
+        // shifted shape. The blur can skip what won't be seen or is known to stay clear.
+        let (buffer_rect, skip_rect) = if inset {
+            (border_box.rect, shadow_box.inner_rect())
+        } else {
+            (shadow_box.rect, border_box.inner_rect())
+        };
+        let blur_radius = AlphaBoxBlur::calculate_blur_radius(Point2D(blur_sigma, blur_sigma));
+        let mut blur = AlphaBoxBlur::new(&buffer_rect, Size2D(0, 0), blur_radius, None,
+                                         Some(&skip_rect));
+        let buffer_rect = blur.rect();
+        let (size, stride) = (blur.size(), blur.stride());
+        if size.width <= 0 || size.height <= 0 {
+            return
+        }
+
+        blur.with_mut_data(|data| {
+            for y in range(0, size.height) {
+                for x in range(0, size.width) {
+                    let coverage = shadow_box.coverage((buffer_rect.origin.x + x) as AzFloat,
+                                                       (buffer_rect.origin.y + y) as AzFloat);
+                    data[(y * stride + x) as uint] = if inset { 255 - coverage } else { coverage };
+                }
+            }
+        });
+        blur.blur();
+
+        let mut mask = None;
+        blur.with_data(|data| {
+            mask = Some(self.create_source_surface_from_data(data, size, stride, A8));
+        });
+
+        // The mask repeats its edges, so the shadow is clipped to the buffer as well as to the
+        // inside or outside of the box. Cutting the box out of a rectangle which contains it
+        // relies on the nonzero winding rule.
+        let origin = Point2D(buffer_rect.origin.x as AzFloat, buffer_rect.origin.y as AzFloat);
+        let buffer_rect = Rect(origin, Size2D(size.width as AzFloat, size.height as AzFloat));
+        let buffer_builder = self.create_path_builder();
+        RoundedRect::new(buffer_rect, &CornerRadii::zero()).add_to_path(&buffer_builder, false);
+        let box_builder = self.create_path_builder();
+        if inset {
+            border_box.add_to_path(&box_builder, false);
+        } else {
+            let bounds = RoundedRect::new(buffer_rect.union(&border_box.rect),
+                                          &CornerRadii::zero());
+            bounds.add_to_path(&box_builder, false);
+            border_box.add_to_path(&box_builder, true);
+        }
+        let buffer_clip = buffer_builder.finish();
+        let box_clip = box_builder.finish();
+
+        self.push_clip(&buffer_clip);
+        self.push_clip(&box_clip);
+        self.mask_surface(&ColorPattern::new(color), mask.unwrap(), origin,
+                          &DrawOptions::new(1.0, 0));
+        self.pop_clip();
+        self.pop_clip();
+    }
 }
 
 // Ugly workaround for the lack of explicit self.
@@ -619,6 +1152,143 @@ pub fn clone_mutable_draw_target(draw_target: &mut DrawTarget) -> DrawTarget {
     return draw_target.clone();
 }
 
+/// Receives the drawing commands issued to recording draw targets.
+pub struct DrawEventRecorder {
+    pub azure_draw_event_recorder: AzDrawEventRecorderRef,
+    in_memory: bool,
+}
+
+impl Drop for DrawEventRecorder {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseDrawEventRecorder(self.azure_draw_event_recorder);
+        }
+    }
+}
+
+impl Clone for DrawEventRecorder {
+    fn clone(&self) -> DrawEventRecorder {
+        unsafe {
+            AzRetainDrawEventRecorder(self.azure_draw_event_recorder);
+        }
+        DrawEventRecorder {
+            azure_draw_event_recorder: self.azure_draw_event_recorder,
+            in_memory: self.in_memory,
+        }
+    }
+}
+
+impl DrawEventRecorder {
+    /// Creates a recorder that writes to the file at `path`, replacing any existing file. Returns
+    /// `None` if the file cannot be opened. The file is complete once the recorder and all draw
+    /// targets recording to it have been dropped.
+    pub fn for_file(path: &str) -> Option<DrawEventRecorder> {
+        let azure_draw_event_recorder = path.to_c_str().with_ref(|path| {
+            unsafe {
+                AzCreateDrawEventRecorderForFile(path)
+            }
+        });
+        if azure_draw_event_recorder == ptr::mut_null() {
+            return None;
+        }
+        Some(DrawEventRecorder {
+            azure_draw_event_recorder: azure_draw_event_recorder,
+            in_memory: false,
+        })
+    }
+
+    /// Creates a recorder that keeps the recording in memory. Use `take_recorded_data` to get at
+    /// it.
+    pub fn new_memory() -> DrawEventRecorder {
+        unsafe {
+            DrawEventRecorder {
+                azure_draw_event_recorder: AzCreateDrawEventRecorderMemory(),
+                in_memory: true,
+            }
+        }
+    }
+
+    /// Returns everything recorded since the last call and empties the recorder, or returns
+    /// `None` if this recorder writes to a file. The first call's data starts with the header;
+    /// concatenating the data from every call gives the complete recording stream. Fonts that
+    /// only the recorder still holds are released first, and their destruction is recorded.
+    pub fn take_recorded_data(&self) -> Option<Vec<u8>> {
+        extern "C" fn copy_recording_data(data: *const u8, length: size_t, baton: *mut c_void) {
+            unsafe {
+                let result: &mut Vec<u8> = mem::transmute(baton);
+                slice::raw::buf_as_slice(data, length as uint, |bytes| {
+                    result.push_all(bytes);
+                })
+            }
+        }
+
+        if !self.in_memory {
+            return None;
+        }
+        let mut result = Vec::new();
+        unsafe {
+            AzDrawEventRecorderMemoryTakeData(self.azure_draw_event_recorder,
+                                              copy_recording_data,
+                                              mem::transmute(&mut result));
+        }
+        Some(result)
+    }
+}
+
+/// Makes every draw target created from now on record to `recorder`, in addition to drawing as
+/// usual, or stops doing so if `recorder` is `None`. Draw targets that already exist are not
+/// affected. Azure keeps its own reference to the recorder, so a file recording is only complete
+/// once this has been called again with `None` and those draw targets have been dropped.
+///
+/// Like any recorder, it keeps a reference to every font drawn through it, as described for
+/// `DrawTarget::new_recording`. A font that nothing else holds any more stays alive until the
+/// next new font is drawn or the recording is taken, or until the recorder is released.
+///
+/// Azure keeps the recorder in a plain static, without any locking. This must not be called while
+/// another task may be creating a draw target: that task can miss the change, or be handed a
+/// recorder that is being released. It also affects every draw target created in the meantime,
+/// whichever task creates it.
+pub fn set_global_recorder(recorder: Option<&DrawEventRecorder>) {
+    unsafe {
+        AzSetGlobalEventRecorder(match recorder {
+            Some(recorder) => recorder.azure_draw_event_recorder,
+            None => ptr::mut_null(),
+        });
+    }
+}
+
+/// Why `replay` could not play a recording back.
+#[deriving(Clone, PartialEq, Show)]
+pub enum ReplayError {
+    /// The recording is malformed or refers to an object it never created.
+    MalformedRecording(RecordingError),
+    /// The backend cannot create draw targets in this build.
+    BackendUnavailable,
+}
+
+impl ReplayError {
+    pub fn description(&self) -> &'static str {
+        match *self {
+            MalformedRecording(error) => error.description(),
+            BackendUnavailable => "the backend is not available",
+        }
+    }
+}
+
+/// Plays back a recording made by a `DrawEventRecorder`, drawing it with `backend`. Returns every
+/// draw target the recording created, in creation order, including ones that were destroyed
+/// before the recording ended.
+pub fn replay(data: &[u8], backend: BackendType) -> Result<Vec<DrawTarget>, ReplayError> {
+    let mut player = DrawEventPlayer::new(backend);
+    if !player.play(data) {
+        return Err(match player.error() {
+            Some(error) => MalformedRecording(error),
+            None => BackendUnavailable,
+        });
+    }
+    Ok(player.draw_targets())
+}
+
 pub struct SourceSurface {
     pub azure_source_surface: AzSourceSurfaceRef,
 }
@@ -676,6 +1346,11 @@ impl SourceSurface {
             }
         }
     }
+
+    /// Writes the contents of this surface as a PNG. See `DataSourceSurface::write_png`.
+    pub fn write_png(&self, w: &mut Writer) -> IoResult<()> {
+        self.get_data_surface().write_png(w)
+    }
 }
 
 impl SourceSurfaceMethods for SourceSurface {
@@ -695,6 +1370,151 @@ impl Drop for DataSourceSurface {
 }
 
 impl DataSourceSurface {
+    /// Creates a new zero-filled surface whose pixels are stored in memory.
+    pub fn new(size: Size2D<i32>, format: SurfaceFormat) -> DataSourceSurface {
+        unsafe {
+            let azure_data_source_surface =
+                AzCreateDataSourceSurface(&mut size.as_azure_int_size(),
+                                          format.as_azure_surface_format());
+            if azure_data_source_surface == ptr::mut_null() {
+                fail!("null azure data source surface");
+            }
+            DataSourceSurface {
+                azure_data_source_surface: azure_data_source_surface
+            }
+        }
+    }
+
+    /// Decodes a PNG into a new surface. Images with an alpha channel or a transparency chunk
+    /// become premultiplied `B8G8R8A8` surfaces; all others become `B8G8R8X8`. Samples are not
+    /// color managed, and 16-bit samples are truncated to 8 bits. Images whose pixels would
+    /// take more than `i32::MAX` bytes are rejected.
+    pub fn from_png(data: &[u8]) -> Result<DataSourceSurface, String> {
+        let image = try!(png::decode(data));
+        let size = Size2D { width: image.width as i32, height: image.height as i32 };
+        let byte_size = if size.width < 0 || size.height < 0 {
+            None
+        } else {
+            size.width.checked_mul(&4).and_then(|stride| stride.checked_mul(&size.height))
+        };
+        if byte_size.is_none() {
+            return Err("PNG dimensions too large".to_string());
+        }
+        let format = if image.has_alpha { B8G8R8A8 } else { B8G8R8X8 };
+        let surface = DataSourceSurface::new(size, format);
+        let stride = surface.stride();
+        surface.with_mut_data(|dst| {
+            convert_pixels(image.pixels.as_slice(), size.width * 4, StraightRGBA,
+                           dst, stride, format.as_pixel_format(),
+                           size);
+        });
+        Ok(surface)
+    }
+
+    /// Resamples this surface into a new surface of the same format and the given size, with a
+    /// higher quality filter than drawing it scaled would use. Returns `None` unless the format
+    /// is `B8G8R8A8` or `B8G8R8X8` and neither size is empty.
+    pub fn resize(&self, size: Size2D<i32>, method: ResizeMethod) -> Option<DataSourceSurface> {
+        let format = self.format();
+        let src_size = self.size();
+        if (format != B8G8R8A8 && format != B8G8R8X8) ||
+                size.width <= 0 || size.height <= 0 ||
+                src_size.width <= 0 || src_size.height <= 0 {
+            return None
+        }
+
+        let result = DataSourceSurface::new(size, format);
+        let (src_stride, dst_stride) = (self.stride(), result.stride());
+        let mut scaled = false;
+        self.with_data(|src| {
+            result.with_mut_data(|dst| {
+                unsafe {
+                    scaled = AzScale(src.as_ptr(),
+                                     &mut src_size.as_azure_int_size(),
+                                     src_stride,
+                                     dst.as_mut_ptr(),
+                                     &mut size.as_azure_int_size(),
+                                     dst_stride,
+                                     format.as_azure_surface_format(),
+                                     method.as_azure_resize_method());
+                }
+            });
+        });
+        if scaled { Some(result) } else { None }
+    }
+
+    /// Halves this surface by averaging blocks of pixels, as many times as it can in each
+    /// direction while staying larger than `size`, and returns the result as a new surface. This
+    /// is much faster than `resize`, and suits building chains of mipmaps for images drawn far
+    /// smaller than their natural size. An odd last row or column is dropped before each
+    /// halving, and a surface which can't be halved at all is copied as it is. Returns `None`
+    /// unless the format is `B8G8R8A8` or `B8G8R8X8` and neither the surface nor `size` is
+    /// empty.
+    pub fn half_scale_for_size(&self, size: Size2D<i32>) -> Option<DataSourceSurface> {
+        let format = self.format();
+        let src_size = self.size();
+        if (format != B8G8R8A8 && format != B8G8R8X8) ||
+                size.width <= 0 || size.height <= 0 ||
+                src_size.width <= 0 || src_size.height <= 0 {
+            return None
+        }
+
+        let src_stride = self.stride();
+        let mut result = None;
+        self.with_data(|src| {
+            unsafe {
+                let scaler = AzCreateImageHalfScaler(src.as_ptr(),
+                                                     src_stride,
+                                                     &mut src_size.as_azure_int_size());
+                AzImageHalfScalerScaleForSize(scaler, &mut size.as_azure_int_size());
+                let scaled_data = AzImageHalfScalerGetScaledData(scaler) as *const u8;
+                let (data, stride, scaled_size) = if scaled_data.is_null() {
+                    (src.as_ptr(), src_stride, src_size)
+                } else {
+                    let scaled_size = AzImageHalfScalerGetSize(scaler);
+                    (scaled_data,
+                     AzImageHalfScalerGetStride(scaler) as i32,
+                     Size2D(scaled_size.width, scaled_size.height))
+                };
+
+                let surface = DataSourceSurface::new(scaled_size, format);
+                let dst_stride = surface.stride();
+                surface.with_mut_data(|dst| {
+                    for y in range(0, scaled_size.height) {
+                        ptr::copy_nonoverlapping_memory(
+                            dst.as_mut_ptr().offset((y * dst_stride) as int),
+                            data.offset((y * stride) as int),
+                            (scaled_size.width * 4) as uint);
+                    }
+                });
+                AzReleaseImageHalfScaler(scaler);
+                result = Some(surface);
+            }
+        });
+        result
+    }
+
+    /// Writes the contents of this surface as an 8-bit PNG. `B8G8R8A8` surfaces are
+    /// unpremultiplied and written as RGBA, `B8G8R8X8` and `R5G6B5` surfaces are written as RGB,
+    /// and `A8` surfaces are written as grayscale images of their alpha values. No color space
+    /// information is attached.
+    pub fn write_png(&self, w: &mut Writer) -> IoResult<()> {
+        let size = self.size();
+        let (color_type, pixels) = match self.format() {
+            B8G8R8A8 => (png::RGBA, self.convert_to(StraightRGBA)),
+            A8 => (png::Grayscale, self.convert_to(AlphaMask)),
+            B8G8R8X8 | R5G6B5 => {
+                let rgbx = self.convert_to(OpaqueRGBX);
+                let mut rgb = Vec::with_capacity(rgbx.len() / 4 * 3);
+                for pixel in rgbx.as_slice().chunks(4) {
+                    rgb.push_all(pixel.slice_to(3));
+                }
+                (png::RGB, rgb)
+            }
+        };
+        png::encode(w, size.width as u32, size.height as u32, color_type, pixels.as_slice())
+    }
+
     pub fn with_data(&self, f: |&[u8]|) {
         unsafe {
             let buf = AzDataSourceSurfaceGetData(self.azure_data_source_surface) as *const u8;
@@ -703,6 +1523,14 @@ impl DataSourceSurface {
         }
     }
 
+    pub fn with_mut_data(&self, f: |&mut [u8]|) {
+        unsafe {
+            let buf = AzDataSourceSurfaceGetData(self.azure_data_source_surface);
+            let len = self.stride() * self.size().height;
+            slice::raw::mut_buf_as_slice(buf, len as uint, f);
+        }
+    }
+
     pub fn stride(&self) -> i32 {
         unsafe {
             AzDataSourceSurfaceGetStride(self.azure_data_source_surface)
@@ -713,6 +1541,20 @@ impl DataSourceSurface {
     pub fn get_size(&self) -> Size2D<i32> {
         self.size()
     }
+
+    /// Returns a copy of this surface's pixels in the given format, tightly packed.
+    pub fn convert_to(&self, format: PixelFormat) -> Vec<u8> {
+        let size = self.size();
+        let src_format = self.format().as_pixel_format();
+        let dst_stride = size.width * format.bytes_per_pixel();
+        let mut result = Vec::from_elem((dst_stride * size.height) as uint, 0u8);
+        self.with_data(|data| {
+            convert_pixels(data, self.stride(), src_format,
+                           result.as_mut_slice(), dst_stride, format,
+                           size);
+        });
+        result
+    }
 }
 
 impl SourceSurfaceMethods for DataSourceSurface {
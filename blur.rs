@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Standalone blurs of 8-bit alpha masks, for rasterizing shadows.
+
+use azure::{AzFloat, AzRect, AzAlphaBoxBlurRef, AzCreateAlphaBoxBlur, AzReleaseAlphaBoxBlur};
+use azure::{AzAlphaBoxBlurGetData, AzAlphaBoxBlurGetSize, AzAlphaBoxBlurGetStride};
+use azure::{AzAlphaBoxBlurGetRect, AzAlphaBoxBlurBlur, AzAlphaBoxBlurCalculateBlurRadius};
+use azure_hl::{AsAzureIntSize, AsAzurePoint, AsAzureRect};
+
+use geom::point::Point2D;
+use geom::rect::Rect;
+use geom::size::Size2D;
+use std::ptr;
+use std::slice;
+
+/// A triple box blur approximating a Gaussian blur, applied in place to an A8 buffer which the
+/// caller draws into first.
+pub struct AlphaBoxBlur {
+    azure_alpha_box_blur: AzAlphaBoxBlurRef,
+}
+
+impl Drop for AlphaBoxBlur {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseAlphaBoxBlur(self.azure_alpha_box_blur);
+        }
+    }
+}
+
+impl AlphaBoxBlur {
+    /// Creates a transparent buffer covering `rect`, in device pixels, grown by `spread_radius`
+    /// and `blur_radius` so that the blur has room to spread out. `blur_radius` should nearly
+    /// always come from `calculate_blur_radius`.
+    ///
+    /// If given, only the part of the buffer needed to draw `dirty_rect` is allocated, and the
+    /// area inside `skip_rect` is left alone by `blur`. Either may make the buffer empty.
+    pub fn new(rect: &Rect<AzFloat>,
+               spread_radius: Size2D<i32>,
+               blur_radius: Size2D<i32>,
+               dirty_rect: Option<&Rect<AzFloat>>,
+               skip_rect: Option<&Rect<AzFloat>>)
+               -> AlphaBoxBlur {
+        let mut dirty_rect = dirty_rect.map(|rect| rect.as_azure_rect());
+        let mut skip_rect = skip_rect.map(|rect| rect.as_azure_rect());
+        unsafe {
+            let azure_alpha_box_blur =
+                AzCreateAlphaBoxBlur(&mut rect.as_azure_rect(),
+                                     &mut spread_radius.as_azure_int_size(),
+                                     &mut blur_radius.as_azure_int_size(),
+                                     match dirty_rect {
+                                         Some(ref mut rect) => rect as *mut AzRect,
+                                         None => ptr::mut_null(),
+                                     },
+                                     match skip_rect {
+                                         Some(ref mut rect) => rect as *mut AzRect,
+                                         None => ptr::mut_null(),
+                                     });
+            AlphaBoxBlur {
+                azure_alpha_box_blur: azure_alpha_box_blur,
+            }
+        }
+    }
+
+    /// Returns the blur radius which approximates a Gaussian blur with the given standard
+    /// deviation along each axis.
+    pub fn calculate_blur_radius(sigma: Point2D<AzFloat>) -> Size2D<i32> {
+        unsafe {
+            let size = AzAlphaBoxBlurCalculateBlurRadius(&mut sigma.as_azure_point());
+            Size2D(size.width, size.height)
+        }
+    }
+
+    /// Returns the area the buffer covers, in device pixels.
+    pub fn rect(&self) -> Rect<i32> {
+        unsafe {
+            let rect = AzAlphaBoxBlurGetRect(self.azure_alpha_box_blur);
+            Rect(Point2D(rect.x, rect.y), Size2D(rect.width, rect.height))
+        }
+    }
+
+    pub fn size(&self) -> Size2D<i32> {
+        unsafe {
+            let size = AzAlphaBoxBlurGetSize(self.azure_alpha_box_blur);
+            Size2D(size.width, size.height)
+        }
+    }
+
+    pub fn stride(&self) -> i32 {
+        unsafe {
+            AzAlphaBoxBlurGetStride(self.azure_alpha_box_blur)
+        }
+    }
+
+    /// Calls `f` with the A8 buffer, `stride() * size().height` bytes long. The buffer is empty
+    /// if nothing needs to be blurred.
+    pub fn with_data(&self, f: |&[u8]|) {
+        unsafe {
+            let buf = AzAlphaBoxBlurGetData(self.azure_alpha_box_blur) as *const u8;
+            if buf.is_null() {
+                return f(&[]);
+            }
+            let len = self.stride() * self.size().height;
+            slice::raw::buf_as_slice(buf, len as uint, f);
+        }
+    }
+
+    /// Calls `f` with the A8 buffer, so the caller can draw what is to be blurred.
+    pub fn with_mut_data(&mut self, f: |&mut [u8]|) {
+        unsafe {
+            let buf = AzAlphaBoxBlurGetData(self.azure_alpha_box_blur);
+            if buf.is_null() {
+                return f(&mut []);
+            }
+            let len = self.stride() * self.size().height;
+            slice::raw::mut_buf_as_slice(buf, len as uint, f);
+        }
+    }
+
+    /// Spreads and blurs the buffer in place.
+    pub fn blur(&mut self) {
+        unsafe {
+            AzAlphaBoxBlurBlur(self.azure_alpha_box_blur);
+        }
+    }
+}
@@ -14,6 +14,7 @@
 namespace mozilla {
 
 template<typename T> class RefCounted;
+template<typename T> class AtomicRefCounted;
 template<typename T> class RefPtr;
 template<typename T> class TemporaryRef;
 template<typename T> class OutParamRef;
@@ -85,6 +86,73 @@ class RefCounted
     int refCnt;
 };
 
+namespace detail {
+#if defined(_MSC_VER)
+extern "C" long __cdecl _InterlockedIncrement(long volatile*);
+extern "C" long __cdecl _InterlockedDecrement(long volatile*);
+#pragma intrinsic(_InterlockedIncrement, _InterlockedDecrement)
+inline int AtomicIncrement(volatile int* aValue) {
+  return int(_InterlockedIncrement(reinterpret_cast<volatile long*>(aValue)));
+}
+inline int AtomicDecrement(volatile int* aValue) {
+  return int(_InterlockedDecrement(reinterpret_cast<volatile long*>(aValue)));
+}
+#else
+inline int AtomicIncrement(volatile int* aValue) {
+  return __sync_add_and_fetch(aValue, 1);
+}
+inline int AtomicDecrement(volatile int* aValue) {
+  return __sync_sub_and_fetch(aValue, 1);
+}
+#endif
+}
+
+/**
+ * AtomicRefCounted<T> is RefCounted<T> for objects that are shared
+ * between threads: AddRef and Release may race with each other, and
+ * the thread that drops the last reference destroys the object.  Only
+ * the refcount is made thread-safe; T must take care of the rest of
+ * its own state.
+ */
+template<typename T>
+class AtomicRefCounted
+{
+    friend class RefPtr<T>;
+
+  protected:
+    AtomicRefCounted() : refCnt(0) { }
+    ~AtomicRefCounted() { MOZ_ASSERT(refCnt == detail::DEAD); }
+
+  public:
+    // Compatibility with nsRefPtr.
+    void AddRef() {
+      MOZ_ASSERT(refCnt >= 0);
+      detail::AtomicIncrement(&refCnt);
+    }
+
+    void Release() {
+      MOZ_ASSERT(refCnt > 0);
+      if (0 == detail::AtomicDecrement(&refCnt)) {
+#ifdef DEBUG
+        refCnt = detail::DEAD;
+#endif
+        delete static_cast<T*>(this);
+      }
+    }
+
+    // Compatibility with wtf::RefPtr.
+    void ref() { AddRef(); }
+    void deref() { Release(); }
+    int refCount() const { return refCnt; }
+    bool hasOneRef() const {
+      MOZ_ASSERT(refCnt > 0);
+      return refCnt == 1;
+    }
+
+  private:
+    volatile int refCnt;
+};
+
 /**
  * RefPtr points to a refcounted thing that has AddRef and Release
  * methods to increase/decrease the refcount, respectively.  After a
@@ -128,7 +128,9 @@ struct StrokeOptions {
  * This structure supplies additional options for calls to DrawSurface.
  *
  * mFilter - Filter used when resampling source surface region to the
- *           destination region.
+ *           destination region. FILTER_GOOD is FILTER_LINEAR, except that
+ *           backends drawing into memory may first resample the source with
+ *           a better filter when it is scaled down a long way.
  * aSamplingBounds - This indicates whether the implementation is allowed
  *                   to sample pixels outside the source rectangle as
  *                   specified in DrawSurface on the surface.
@@ -468,8 +470,13 @@ struct GlyphBuffer
 /* This class is an abstraction of a backend/platform specific font object
  * at a particular size. It is passed into text drawing calls to describe
  * the font used for the drawing call.
+ *
+ * A ScaledFont may be shared between threads: its refcount is atomic and
+ * the backends never change a font once it has been created. User data is
+ * not locked, so it must only be touched on the thread that created the
+ * font; nothing in Moz2D itself uses it.
  */
-class ScaledFont : public RefCounted<ScaledFont>
+class ScaledFont : public AtomicRefCounted<ScaledFont>
 {
 public:
   virtual ~ScaledFont() {}
@@ -519,6 +526,7 @@ struct FontOptions
   FontStyle mStyle;
   uint8_t *mData;
   uint32_t mDataSize;
+  uint32_t mFaceIndex;
 };
 #endif
 
@@ -919,8 +927,33 @@ public:
   static TemporaryRef<DrawEventRecorder>
     CreateEventRecorderForFile(const char *aFilename);
 
+  /*
+   * Makes every draw target created by the factory from now on record to
+   * aRecorder, or stops recording if aRecorder is null. Draw targets which
+   * already exist are not affected. The recorder is a plain static, so this
+   * must not be called while another thread may be creating a draw target.
+   */
   static void SetGlobalEventRecorder(DrawEventRecorder *aRecorder);
 
+#ifdef USE_SKIA
+  /*
+   * Glyph rendering options for Skia draw targets. aSubpixelPositioning
+   * places glyphs at fractional positions instead of rounding them to whole
+   * pixels.
+   */
+  static TemporaryRef<GlyphRenderingOptions>
+    CreateSkiaGlyphRenderingOptions(FontHinting aHinting, bool aSubpixelPositioning);
+#endif
+
+#ifdef XP_MACOSX
+  /*
+   * Glyph rendering options for CoreGraphics draw targets, which leave
+   * hinting to the system.
+   */
+  static TemporaryRef<GlyphRenderingOptions>
+    CreateCGGlyphRenderingOptions(bool aSubpixelPositioning);
+#endif
+
 #ifdef USE_SKIA_GPU
   static TemporaryRef<DrawTarget>
     CreateSkiaDrawTargetForFBO(unsigned int aFBOID, GrContext *aContext, const IntSize &aSize, SurfaceFormat aFormat);
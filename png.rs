@@ -0,0 +1,339 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A small PNG encoder and decoder, used to dump surfaces for debugging and reference tests.
+//!
+//! Only non-interlaced images with a bit depth of 8 or 16 are decoded. Color management chunks
+//! (`gAMA`, `cHRM`, `sRGB`, `iCCP`) are ignored on input and never written on output: samples
+//! are passed through unchanged, which matches how Azure itself treats pixel values.
+
+use flate;
+use std::io::{IoResult, Writer};
+
+static SIGNATURE: [u8, ..8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+
+#[deriving(Clone, PartialEq)]
+pub enum ColorType {
+    Grayscale,
+    RGB,
+    Palette,
+    GrayscaleAlpha,
+    RGBA,
+}
+
+impl ColorType {
+    fn from_png_value(value: u8) -> Option<ColorType> {
+        match value {
+            0 => Some(Grayscale),
+            2 => Some(RGB),
+            3 => Some(Palette),
+            4 => Some(GrayscaleAlpha),
+            6 => Some(RGBA),
+            _ => None,
+        }
+    }
+
+    fn png_value(self) -> u8 {
+        match self {
+            Grayscale      => 0,
+            RGB            => 2,
+            Palette        => 3,
+            GrayscaleAlpha => 4,
+            RGBA           => 6,
+        }
+    }
+
+    pub fn channels(self) -> uint {
+        match self {
+            Grayscale | Palette => 1,
+            GrayscaleAlpha      => 2,
+            RGB                 => 3,
+            RGBA                => 4,
+        }
+    }
+}
+
+/// A decoded image, always expanded to 8-bit RGBA with straight (non-premultiplied) alpha.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// True if the source image had an alpha channel or a transparency chunk.
+    pub has_alpha: bool,
+    pub pixels: Vec<u8>,
+}
+
+fn crc32_table() -> [u32, ..256] {
+    let mut table = [0u32, ..256];
+    for n in range(0u, 256) {
+        let mut c = n as u32;
+        for _ in range(0u, 8) {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+        }
+        table[n] = c;
+    }
+    table
+}
+
+fn crc32(table: &[u32, ..256], chunks: &[&[u8]]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for chunk in chunks.iter() {
+        for &byte in chunk.iter() {
+            crc = table[((crc ^ byte as u32) & 0xff) as uint] ^ (crc >> 8);
+        }
+    }
+    crc ^ 0xffffffff
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32 << 24) | (bytes[1] as u32 << 16) | (bytes[2] as u32 << 8) | bytes[3] as u32
+}
+
+fn write_chunk(w: &mut Writer, table: &[u32, ..256], kind: &[u8], data: &[u8]) -> IoResult<()> {
+    try!(w.write_be_u32(data.len() as u32));
+    try!(w.write(kind));
+    try!(w.write(data));
+    w.write_be_u32(crc32(table, [kind, data]))
+}
+
+/// Writes `pixels`, which holds `height` rows of `width` 8-bit samples per channel, as a PNG.
+pub fn encode(w: &mut Writer, width: u32, height: u32, color_type: ColorType, pixels: &[u8])
+              -> IoResult<()> {
+    assert!(color_type != Palette);
+    let row_len = width as uint * color_type.channels();
+    assert!(pixels.len() == row_len * height as uint);
+
+    let table = crc32_table();
+
+    let mut header = Vec::with_capacity(13);
+    for &value in [width, height].iter() {
+        header.push((value >> 24) as u8);
+        header.push((value >> 16) as u8);
+        header.push((value >> 8) as u8);
+        header.push(value as u8);
+    }
+    // Bit depth, color type, compression, filter and interlace methods.
+    header.push_all([8, color_type.png_value(), 0, 0, 0]);
+
+    // Every scanline gets filter type 0 (None); the images we dump are small enough that
+    // picking better filters is not worth the complexity.
+    let mut scanlines = Vec::with_capacity((row_len + 1) * height as uint);
+    for row in pixels.chunks(row_len) {
+        scanlines.push(0u8);
+        scanlines.push_all(row);
+    }
+    let compressed = match flate::deflate_bytes_zlib(scanlines.as_slice()) {
+        Some(compressed) => compressed,
+        None => fail!("png::encode(): deflate failed"),
+    };
+
+    try!(w.write(SIGNATURE));
+    try!(write_chunk(w, &table, b"IHDR", header.as_slice()));
+    try!(write_chunk(w, &table, b"IDAT", compressed.as_slice()));
+    write_chunk(w, &table, b"IEND", [])
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverses the per-scanline filters in place. `data` holds a filter type byte followed by
+/// `row_len` bytes for each row.
+fn unfilter(data: &mut [u8], row_len: uint, bpp: uint, height: uint) -> Result<(), String> {
+    let stride = row_len + 1;
+    for y in range(0, height) {
+        let row_start = y * stride + 1;
+        let filter = data[row_start - 1];
+        for x in range(0, row_len) {
+            let i = row_start + x;
+            let left = if x >= bpp { data[i - bpp] } else { 0 };
+            let up = if y > 0 { data[i - stride] } else { 0 };
+            let up_left = if y > 0 && x >= bpp { data[i - stride - bpp] } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("unknown PNG filter type {}", filter)),
+            };
+            data[i] = data[i] + predictor;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a PNG image into 8-bit RGBA with straight alpha.
+pub fn decode(data: &[u8]) -> Result<Image, String> {
+    if data.len() < SIGNATURE.len() || data.slice_to(SIGNATURE.len()) != SIGNATURE.as_slice() {
+        return Err("not a PNG file".to_string());
+    }
+
+    let mut header: Option<(u32, u32, u8, ColorType, u8)> = None;
+    let mut palette: Vec<u8> = Vec::new();
+    let mut transparency: Vec<u8> = Vec::new();
+    let mut compressed: Vec<u8> = Vec::new();
+    let mut seen_end = false;
+
+    let mut pos = SIGNATURE.len();
+    while pos < data.len() {
+        if data.len() - pos < 12 {
+            return Err("truncated PNG chunk".to_string());
+        }
+        let length = be_u32(data.slice_from(pos)) as uint;
+        let kind = data.slice(pos + 4, pos + 8);
+        if length > data.len() - pos - 12 {
+            return Err("PNG chunk length exceeds file size".to_string());
+        }
+        let body = data.slice(pos + 8, pos + 8 + length);
+        pos += 12 + length;
+
+        if kind == b"IHDR" {
+            if length != 13 {
+                return Err("bad IHDR chunk".to_string());
+            }
+            let color_type = match ColorType::from_png_value(body[9]) {
+                Some(color_type) => color_type,
+                None => return Err(format!("unknown PNG color type {}", body[9])),
+            };
+            if body[10] != 0 || body[11] != 0 {
+                return Err("unknown PNG compression or filter method".to_string());
+            }
+            header = Some((be_u32(body), be_u32(body.slice_from(4)), body[8], color_type,
+                           body[12]));
+        } else if kind == b"PLTE" {
+            palette.push_all(body);
+        } else if kind == b"tRNS" {
+            transparency.push_all(body);
+        } else if kind == b"IDAT" {
+            compressed.push_all(body);
+        } else if kind == b"IEND" {
+            seen_end = true;
+            break;
+        }
+    }
+
+    if !seen_end {
+        return Err("missing IEND chunk".to_string());
+    }
+    let (width, height, depth, color_type, interlace) = match header {
+        Some(header) => header,
+        None => return Err("missing IHDR chunk".to_string()),
+    };
+    if interlace != 0 {
+        return Err("interlaced PNGs are not supported".to_string());
+    }
+    if depth != 8 && (depth != 16 || color_type == Palette) {
+        return Err(format!("unsupported PNG bit depth {}", depth));
+    }
+    if color_type == Palette && (palette.len() == 0 || palette.len() % 3 != 0) {
+        return Err("missing or malformed PLTE chunk".to_string());
+    }
+
+    // For grayscale and RGB images, a transparency chunk names one 16-bit sample value per
+    // channel; pixels matching it exactly are fully transparent. Other lengths are ignored.
+    let transparent_color: Option<Vec<u16>> = match color_type {
+        Grayscale | RGB if transparency.len() == color_type.channels() * 2 => {
+            Some(transparency.as_slice().chunks(2).map(|pair| {
+                (pair[0] as u16 << 8) | pair[1] as u16
+            }).collect())
+        }
+        _ => None,
+    };
+
+    let channels = color_type.channels();
+    let sample_size = depth as uint / 8;
+    let bpp = channels * sample_size;
+    let row_len = match (width as uint).checked_mul(&bpp) {
+        Some(row_len) => row_len,
+        None => return Err("PNG dimensions too large".to_string()),
+    };
+    let expected = match (row_len + 1).checked_mul(&(height as uint)) {
+        Some(expected) => expected,
+        None => return Err("PNG dimensions too large".to_string()),
+    };
+
+    let inflated = match flate::inflate_bytes_zlib(compressed.as_slice()) {
+        Some(inflated) => inflated,
+        None => return Err("corrupt PNG image data".to_string()),
+    };
+    if inflated.len() < expected {
+        return Err("truncated PNG image data".to_string());
+    }
+    let mut scanlines = Vec::from_slice(inflated.as_slice().slice_to(expected));
+    try!(unfilter(scanlines.as_mut_slice(), row_len, bpp, height as uint));
+
+    let scanlines = scanlines.as_slice();
+    let mut pixels = Vec::with_capacity(width as uint * height as uint * 4);
+    for y in range(0, height as uint) {
+        let row = scanlines.slice(y * (row_len + 1) + 1, (y + 1) * (row_len + 1));
+        for x in range(0, width as uint) {
+            // For 16-bit images we simply keep the most significant byte of each sample.
+            let sample = |c: uint| row[(x * channels + c) * sample_size];
+            let mut opacity = 0xffu8;
+            match transparent_color {
+                Some(ref color) => {
+                    let matches = range(0, channels).all(|c| {
+                        let i = (x * channels + c) * sample_size;
+                        let value = if sample_size == 2 {
+                            (row[i] as u16 << 8) | row[i + 1] as u16
+                        } else {
+                            row[i] as u16
+                        };
+                        value == color.as_slice()[c]
+                    });
+                    if matches {
+                        opacity = 0;
+                    }
+                }
+                None => {}
+            }
+            match color_type {
+                Grayscale => {
+                    let gray = sample(0);
+                    pixels.push_all([gray, gray, gray, opacity]);
+                }
+                GrayscaleAlpha => {
+                    let gray = sample(0);
+                    pixels.push_all([gray, gray, gray, sample(1)]);
+                }
+                RGB => pixels.push_all([sample(0), sample(1), sample(2), opacity]),
+                RGBA => pixels.push_all([sample(0), sample(1), sample(2), sample(3)]),
+                Palette => {
+                    let index = sample(0) as uint;
+                    if index * 3 + 2 >= palette.len() {
+                        return Err("PNG palette index out of range".to_string());
+                    }
+                    let alpha = if index < transparency.len() {
+                        transparency.as_slice()[index]
+                    } else {
+                        0xff
+                    };
+                    let entry = palette.slice(index * 3, index * 3 + 3);
+                    pixels.push_all([entry[0], entry[1], entry[2], alpha]);
+                }
+            }
+        }
+    }
+
+    Ok(Image {
+        width: width,
+        height: height,
+        has_alpha: match color_type {
+            GrayscaleAlpha | RGBA => true,
+            Palette => transparency.len() > 0,
+            Grayscale | RGB => transparent_color.is_some(),
+        },
+        pixels: pixels,
+    })
+}
@@ -0,0 +1,401 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Inspection and playback of recordings made by a `DrawEventRecorder`.
+
+use azure::{AzColor, AzRect, AzPoint, AzRecordedEventDescription, AzRecordedEventReaderRef};
+use azure::{AzCreateRecordedEventReader, AzReleaseRecordedEventReader, AzRecordedEventReaderNext};
+use azure::{AzRecordedEventReaderHasFailed, AzRecordedEventReaderGetError};
+use azure::{AzRecordedEventReaderGetEventInfo};
+use azure::{AzDrawEventPlayerRef, AzCreateDrawEventPlayer, AzReleaseDrawEventPlayer};
+use azure::{AzDrawEventPlayerPlay, AzDrawEventPlayerGetError, AzDrawEventPlayerPlayCurrentEvent};
+use azure::{AzDrawEventPlayerPlayInput};
+use azure::{AzDrawEventPlayerGetDrawTargetCount, AzDrawEventPlayerGetDrawTarget};
+use azure::{AZ_PATTERN_COLOR, AZ_PATTERN_SURFACE, AZ_PATTERN_LINEAR_GRADIENT};
+use azure::{AZ_PATTERN_RADIAL_GRADIENT};
+use azure::{AZ_EVENT_DRAWTARGETCREATION, AZ_EVENT_DRAWTARGETDESTRUCTION, AZ_EVENT_FILLRECT};
+use azure::{AZ_EVENT_STROKERECT, AZ_EVENT_STROKELINE, AZ_EVENT_CLEARRECT, AZ_EVENT_COPYSURFACE};
+use azure::{AZ_EVENT_SETTRANSFORM, AZ_EVENT_PUSHCLIP, AZ_EVENT_PUSHCLIPRECT, AZ_EVENT_POPCLIP};
+use azure::{AZ_EVENT_FILL, AZ_EVENT_FILLGLYPHS, AZ_EVENT_MASK, AZ_EVENT_STROKE};
+use azure::{AZ_EVENT_DRAWSURFACE, AZ_EVENT_DRAWSURFACEWITHSHADOW, AZ_EVENT_PATHCREATION};
+use azure::{AZ_EVENT_PATHDESTRUCTION, AZ_EVENT_SOURCESURFACECREATION};
+use azure::{AZ_EVENT_SOURCESURFACEDESTRUCTION, AZ_EVENT_GRADIENTSTOPSCREATION};
+use azure::{AZ_EVENT_GRADIENTSTOPSDESTRUCTION, AZ_EVENT_SNAPSHOT, AZ_EVENT_SCALEDFONTCREATION};
+use azure::{AZ_EVENT_SCALEDFONTDESTRUCTION};
+use azure::{AzRecordingError, AZ_RECORDING_ERROR_NONE, AZ_RECORDING_ERROR_BAD_HEADER};
+use azure::{AZ_RECORDING_ERROR_UNSUPPORTED_VERSION, AZ_RECORDING_ERROR_UNKNOWN_EVENT};
+use azure::{AZ_RECORDING_ERROR_TRUNCATED, AZ_RECORDING_ERROR_INVALID_EVENT};
+use azure::{AZ_RECORDING_ERROR_UNKNOWN_REFERENCE};
+use azure_hl::{BackendType, Color, DrawTarget, SurfaceFormat};
+
+use geom::matrix2d::Matrix2D;
+use geom::point::Point2D;
+use geom::rect::Rect;
+use geom::size::Size2D;
+use libc::{c_void, size_t};
+use std::c_str::CString;
+use std::mem;
+use std::slice;
+
+/// Identifies an object in the recording: a draw target, path, source surface, gradient stops or
+/// scaled font. These are the addresses the objects had in the recording process, so they are
+/// only meaningful for matching events within one recording.
+pub type ObjectRef = u64;
+
+/// Why a recording could not be read to its end.
+#[deriving(Clone, PartialEq, Show)]
+pub enum RecordingError {
+    /// The data does not start with a recording header.
+    BadHeader,
+    /// The recording was made by an incompatible version of Azure.
+    UnsupportedVersion,
+    UnknownEvent,
+    /// The recording ends in the middle of an event.
+    TruncatedEvent,
+    /// An event has an impossible length, size or enum value, or creates a draw target or source
+    /// surface larger than Azure allows in recordings.
+    InvalidEvent,
+    /// An event refers to an object that was never created or was already destroyed. Only
+    /// reported by `DrawEventPlayer`.
+    UnknownReference,
+}
+
+impl RecordingError {
+    fn from_azure_recording_error(error: AzRecordingError) -> Option<RecordingError> {
+        match error {
+            AZ_RECORDING_ERROR_NONE => None,
+            AZ_RECORDING_ERROR_BAD_HEADER => Some(BadHeader),
+            AZ_RECORDING_ERROR_UNSUPPORTED_VERSION => Some(UnsupportedVersion),
+            AZ_RECORDING_ERROR_UNKNOWN_EVENT => Some(UnknownEvent),
+            AZ_RECORDING_ERROR_TRUNCATED => Some(TruncatedEvent),
+            AZ_RECORDING_ERROR_INVALID_EVENT => Some(InvalidEvent),
+            AZ_RECORDING_ERROR_UNKNOWN_REFERENCE => Some(UnknownReference),
+            _ => fail!("RecordingError::from_azure_recording_error(): unknown Azure error"),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match *self {
+            BadHeader => "not a draw event recording",
+            UnsupportedVersion => "recorded by an incompatible version of Azure",
+            UnknownEvent => "unknown event type",
+            TruncatedEvent => "recording ends in the middle of an event",
+            InvalidEvent => "event with an invalid length, size or value",
+            UnknownReference => "event refers to an object that does not exist",
+        }
+    }
+}
+
+/// The kind of pattern a drawing event uses.
+#[deriving(Clone)]
+pub enum PatternKind {
+    ColorPatternKind(Color),
+    SurfacePatternKind,
+    LinearGradientPatternKind,
+    RadialGradientPatternKind,
+}
+
+/// A recorded event. Drawing events start with the draw target they draw to; creation and
+/// destruction events start with the object they create or destroy.
+#[deriving(Clone)]
+pub enum RecordedEvent {
+    DrawTargetCreation(ObjectRef, BackendType, Size2D<i32>, SurfaceFormat),
+    DrawTargetDestruction(ObjectRef),
+    FillRect(ObjectRef, Rect<f32>, PatternKind),
+    /// The draw target, the rectangle, the pattern and the stroke width.
+    StrokeRect(ObjectRef, Rect<f32>, PatternKind, f32),
+    /// The draw target, the start and end points, the pattern and the stroke width.
+    StrokeLine(ObjectRef, Point2D<f32>, Point2D<f32>, PatternKind, f32),
+    ClearRect(ObjectRef, Rect<f32>),
+    /// The draw target, the source surface, the source rectangle and the destination point.
+    CopySurface(ObjectRef, ObjectRef, Rect<i32>, Point2D<i32>),
+    SetTransform(ObjectRef, Matrix2D<f32>),
+    /// The draw target and the path clipped to.
+    PushClip(ObjectRef, ObjectRef),
+    PushClipRect(ObjectRef, Rect<f32>),
+    PopClip(ObjectRef),
+    /// The draw target, the path and the pattern.
+    Fill(ObjectRef, ObjectRef, PatternKind),
+    /// The draw target, the scaled font, the pattern and the number of glyphs.
+    FillGlyphs(ObjectRef, ObjectRef, PatternKind, uint),
+    /// The draw target, the source pattern and the mask pattern.
+    Mask(ObjectRef, PatternKind, PatternKind),
+    /// The draw target, the path, the pattern and the stroke width.
+    Stroke(ObjectRef, ObjectRef, PatternKind, f32),
+    /// The draw target, the source surface, the destination and the source rectangles.
+    DrawSurface(ObjectRef, ObjectRef, Rect<f32>, Rect<f32>),
+    /// The draw target, the source surface, the destination point, the shadow color, the shadow
+    /// offset and the shadow's blur sigma.
+    DrawSurfaceWithShadow(ObjectRef, ObjectRef, Point2D<f32>, Color, Point2D<f32>, f32),
+    /// The path and the number of path operations in it.
+    PathCreation(ObjectRef, uint),
+    PathDestruction(ObjectRef),
+    SourceSurfaceCreation(ObjectRef, Size2D<i32>, SurfaceFormat),
+    SourceSurfaceDestruction(ObjectRef),
+    /// The gradient stops and the number of stops.
+    GradientStopsCreation(ObjectRef, uint),
+    GradientStopsDestruction(ObjectRef),
+    /// The snapshot and the draw target it was taken of.
+    Snapshot(ObjectRef, ObjectRef),
+    /// The scaled font and its glyph size.
+    ScaledFontCreation(ObjectRef, f32),
+    ScaledFontDestruction(ObjectRef),
+}
+
+impl RecordedEvent {
+    /// Returns the draw target this event draws to, or `None` if it is not a drawing event.
+    pub fn draw_target(&self) -> Option<ObjectRef> {
+        match *self {
+            FillRect(dt, _, _) | StrokeRect(dt, _, _, _) | StrokeLine(dt, _, _, _, _) |
+            ClearRect(dt, _) | CopySurface(dt, _, _, _) | SetTransform(dt, _) |
+            PushClip(dt, _) | PushClipRect(dt, _) | PopClip(dt) | Fill(dt, _, _) |
+            FillGlyphs(dt, _, _, _) | Mask(dt, _, _) | Stroke(dt, _, _, _) |
+            DrawSurface(dt, _, _, _) | DrawSurfaceWithShadow(dt, _, _, _, _, _) => Some(dt),
+            _ => None,
+        }
+    }
+
+    fn from_azure_description(desc: &AzRecordedEventDescription) -> RecordedEvent {
+        let object = desc.mObject;
+        let reference = desc.mReference;
+        let pattern = || pattern_kind(desc.mPatternType, &desc.mColor);
+        let rect = rect(&desc.mRect);
+        let point1 = point(&desc.mPoint1);
+        let point2 = point(&desc.mPoint2);
+        let size = Size2D(desc.mSize.width, desc.mSize.height);
+        let count = desc.mCount as uint;
+
+        match desc.mType as u32 {
+            AZ_EVENT_DRAWTARGETCREATION => {
+                DrawTargetCreation(object,
+                                   BackendType::new(desc.mBackendType as u32),
+                                   size,
+                                   SurfaceFormat::new(desc.mFormat as u32))
+            }
+            AZ_EVENT_DRAWTARGETDESTRUCTION => DrawTargetDestruction(object),
+            AZ_EVENT_FILLRECT => FillRect(object, rect, pattern()),
+            AZ_EVENT_STROKERECT => StrokeRect(object, rect, pattern(), desc.mValue),
+            AZ_EVENT_STROKELINE => StrokeLine(object, point1, point2, pattern(), desc.mValue),
+            AZ_EVENT_CLEARRECT => ClearRect(object, rect),
+            AZ_EVENT_COPYSURFACE => {
+                let source = &desc.mSourceRect;
+                CopySurface(object,
+                            reference,
+                            Rect(Point2D(source.x as i32, source.y as i32),
+                                 Size2D(source.width as i32, source.height as i32)),
+                            Point2D(point1.x as i32, point1.y as i32))
+            }
+            AZ_EVENT_SETTRANSFORM => unsafe { SetTransform(object, mem::transmute(desc.mTransform)) },
+            AZ_EVENT_PUSHCLIP => PushClip(object, reference),
+            AZ_EVENT_PUSHCLIPRECT => PushClipRect(object, rect),
+            AZ_EVENT_POPCLIP => PopClip(object),
+            AZ_EVENT_FILL => Fill(object, reference, pattern()),
+            AZ_EVENT_FILLGLYPHS => FillGlyphs(object, reference, pattern(), count),
+            AZ_EVENT_MASK => {
+                Mask(object, pattern(), pattern_kind(desc.mMaskPatternType, &desc.mMaskColor))
+            }
+            AZ_EVENT_STROKE => Stroke(object, reference, pattern(), desc.mValue),
+            AZ_EVENT_DRAWSURFACE => {
+                DrawSurface(object, reference, rect, self::rect(&desc.mSourceRect))
+            }
+            AZ_EVENT_DRAWSURFACEWITHSHADOW => {
+                DrawSurfaceWithShadow(object, reference, point1, color(&desc.mColor), point2,
+                                      desc.mValue)
+            }
+            AZ_EVENT_PATHCREATION => PathCreation(object, count),
+            AZ_EVENT_PATHDESTRUCTION => PathDestruction(object),
+            AZ_EVENT_SOURCESURFACECREATION => {
+                SourceSurfaceCreation(object, size, SurfaceFormat::new(desc.mFormat as u32))
+            }
+            AZ_EVENT_SOURCESURFACEDESTRUCTION => SourceSurfaceDestruction(object),
+            AZ_EVENT_GRADIENTSTOPSCREATION => GradientStopsCreation(object, count),
+            AZ_EVENT_GRADIENTSTOPSDESTRUCTION => GradientStopsDestruction(object),
+            AZ_EVENT_SNAPSHOT => Snapshot(object, reference),
+            AZ_EVENT_SCALEDFONTCREATION => ScaledFontCreation(object, desc.mValue),
+            AZ_EVENT_SCALEDFONTDESTRUCTION => ScaledFontDestruction(object),
+            _ => fail!("RecordedEvent::from_azure_description(): unknown event type"),
+        }
+    }
+}
+
+fn color(color: &AzColor) -> Color {
+    Color::new(color.r, color.g, color.b, color.a)
+}
+
+fn rect(rect: &AzRect) -> Rect<f32> {
+    Rect(Point2D(rect.x, rect.y), Size2D(rect.width, rect.height))
+}
+
+fn point(point: &AzPoint) -> Point2D<f32> {
+    Point2D(point.x, point.y)
+}
+
+fn pattern_kind(pattern_type: i32, pattern_color: &AzColor) -> PatternKind {
+    match pattern_type as u32 {
+        AZ_PATTERN_COLOR => ColorPatternKind(color(pattern_color)),
+        AZ_PATTERN_SURFACE => SurfacePatternKind,
+        AZ_PATTERN_LINEAR_GRADIENT => LinearGradientPatternKind,
+        AZ_PATTERN_RADIAL_GRADIENT => RadialGradientPatternKind,
+        _ => fail!("pattern_kind(): unknown Azure pattern type"),
+    }
+}
+
+/// Iterates over the events of a recording. Iteration stops early if the recording is malformed;
+/// use `error` to find out whether and why that happened. Recordings are checked as they are
+/// read, so it is safe to iterate over recordings from untrusted sources.
+pub struct RecordedEvents {
+    azure_recorded_event_reader: AzRecordedEventReaderRef,
+}
+
+impl Drop for RecordedEvents {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseRecordedEventReader(self.azure_recorded_event_reader);
+        }
+    }
+}
+
+impl RecordedEvents {
+    /// Starts reading the recording in `data`, which must include the recording header.
+    pub fn new(data: &[u8]) -> RecordedEvents {
+        unsafe {
+            RecordedEvents {
+                azure_recorded_event_reader: AzCreateRecordedEventReader(data.as_ptr(),
+                                                                         data.len() as size_t),
+            }
+        }
+    }
+
+    /// Returns true if the recording had an unsupported header or a malformed event.
+    pub fn failed(&self) -> bool {
+        unsafe {
+            AzRecordedEventReaderHasFailed(self.azure_recorded_event_reader)
+        }
+    }
+
+    /// Returns why iteration stopped before the end of the recording, if it did.
+    pub fn error(&self) -> Option<RecordingError> {
+        unsafe {
+            RecordingError::from_azure_recording_error(
+                AzRecordedEventReaderGetError(self.azure_recorded_event_reader))
+        }
+    }
+
+    /// Returns a one line, human readable description of the event most recently returned by
+    /// `next`, in the format used by Gecko's player2d.
+    pub fn current_info(&self) -> String {
+        unsafe {
+            let info = CString::new(AzRecordedEventReaderGetEventInfo(self.azure_recorded_event_reader),
+                                    false);
+            match info.as_str() {
+                Some(info) => info.to_string(),
+                None => fail!("event info is not valid UTF-8"),
+            }
+        }
+    }
+}
+
+impl Iterator<RecordedEvent> for RecordedEvents {
+    fn next(&mut self) -> Option<RecordedEvent> {
+        unsafe {
+            let mut desc: AzRecordedEventDescription = mem::zeroed();
+            if !AzRecordedEventReaderNext(self.azure_recorded_event_reader, &mut desc) {
+                return None;
+            }
+            Some(RecordedEvent::from_azure_description(&desc))
+        }
+    }
+}
+
+/// Plays back recordings onto draw targets of a single backend. Draw targets created by the
+/// recording stay alive after the recording destroys them, so they can be inspected afterwards.
+pub struct DrawEventPlayer {
+    azure_draw_event_player: AzDrawEventPlayerRef,
+}
+
+impl Drop for DrawEventPlayer {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseDrawEventPlayer(self.azure_draw_event_player);
+        }
+    }
+}
+
+impl DrawEventPlayer {
+    pub fn new(backend: BackendType) -> DrawEventPlayer {
+        unsafe {
+            DrawEventPlayer {
+                azure_draw_event_player: AzCreateDrawEventPlayer(backend.as_azure_backend_type()),
+            }
+        }
+    }
+
+    /// Plays a complete recording, header included. Returns false if the recording is malformed,
+    /// an event refers to an object the recording never created, or the backend is unavailable;
+    /// events before the problem have still been played, and `error` tells which happened.
+    pub fn play(&mut self, data: &[u8]) -> bool {
+        unsafe {
+            AzDrawEventPlayerPlay(self.azure_draw_event_player, data.as_ptr(), data.len() as size_t)
+        }
+    }
+
+    /// Like `play`, but reads the recording from `reader` as it goes, so it can come from a pipe or
+    /// socket. A read error ends the recording, which is then reported as truncated unless it
+    /// happened between two events.
+    pub fn play_from(&mut self, reader: &mut Reader) -> bool {
+        extern "C" fn read_recording_data(buffer: *mut u8, length: size_t, baton: *mut c_void)
+                                          -> size_t {
+            unsafe {
+                let reader: &mut &mut Reader = mem::transmute(baton);
+                slice::raw::mut_buf_as_slice(buffer, length as uint, |buffer| {
+                    match reader.read_at_least(1, buffer) {
+                        Ok(length) => length as size_t,
+                        Err(_) => 0,
+                    }
+                })
+            }
+        }
+
+        let mut reader = reader;
+        unsafe {
+            AzDrawEventPlayerPlayInput(self.azure_draw_event_player,
+                                       read_recording_data,
+                                       mem::transmute(&mut reader))
+        }
+    }
+
+    /// Returns why the last call to `play`, `play_from` or `play_current` stopped, or `None` if it
+    /// played everything or the backend was unavailable.
+    pub fn error(&self) -> Option<RecordingError> {
+        unsafe {
+            RecordingError::from_azure_recording_error(
+                AzDrawEventPlayerGetError(self.azure_draw_event_player))
+        }
+    }
+
+    /// Plays the event most recently returned by `events.next()`. Returns false if there is no
+    /// such event, it refers to an object this player doesn't have, it would take the draw
+    /// targets this player created past their total size limit, or the backend is unavailable.
+    pub fn play_current(&mut self, events: &RecordedEvents) -> bool {
+        unsafe {
+            AzDrawEventPlayerPlayCurrentEvent(self.azure_draw_event_player,
+                                              events.azure_recorded_event_reader)
+        }
+    }
+
+    /// Returns every draw target created so far, in creation order.
+    pub fn draw_targets(&self) -> Vec<DrawTarget> {
+        unsafe {
+            let count = AzDrawEventPlayerGetDrawTargetCount(self.azure_draw_event_player);
+            range(0, count).map(|i| {
+                DrawTarget {
+                    azure_draw_target: AzDrawEventPlayerGetDrawTarget(self.azure_draw_event_player,
+                                                                      i),
+                    data: None,
+                    skia_context: None
+                }
+            }).collect()
+        }
+    }
+}
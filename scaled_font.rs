@@ -2,15 +2,28 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use azure::{AzScaledFontRef, AzFloat};
-use azure::{struct__AzNativeFont};
+use azure::{AzScaledFontRef, AzFloat, AzGlyph, AzGlyphBuffer, AzGlyphRenderingOptionsRef};
+use azure::{struct__AzNativeFont, struct__AzGlyph, struct__AzGlyphBuffer};
 
-use azure_hl::{BackendType,SkiaBackend};
-use azure::{AzCreateScaledFontForNativeFont, AzReleaseScaledFont};
+use azure_hl::{BackendType,SkiaBackend,CoreGraphicsBackend,CoreGraphicsAcceleratedBackend};
+use azure_hl::{AsAzurePoint, DrawTarget, Path, PathBuilder};
+use azure::{AzCreateScaledFontForNativeFont, AzCreateScaledFontForTrueTypeData};
+use azure::{AzFontType, AzFontStyle, AzRetainScaledFont, AzReleaseScaledFont};
+use azure::{AzScaledFontGetPathForGlyphs, AzScaledFontCopyGlyphsToBuilder};
+use azure::{AzScaledFontGetFontFileData, AzScaledFontGetFontMetrics};
+use azure::{AzScaledFontGetGlyphAdvance, AzScaledFontGetGlyphBounds, AzScaledFontGetGlyphIndices};
+use azure::{AzFontHinting, AzCreateSkiaGlyphRenderingOptions, AzCreateCGGlyphRenderingOptions};
+use azure::{AzReleaseGlyphRenderingOptions};
+
+use geom::point::Point2D;
+use geom::rect::Rect;
+use geom::size::Size2D;
 
 use libc::c_void;
+use std::c_str::ToCStr;
 use std::mem;
 use std::ptr;
+use std::slice;
 
 #[cfg(target_os="macos")]
 use scaled_font::macos::*;
@@ -49,11 +62,143 @@ pub type SkTypeface = *mut c_void;
 
 #[cfg(target_os="linux")]
 #[cfg(target_os="android")]
-pub enum FontInfo<'a> {
+pub enum FontInfo {
     NativeFont(FT_Face),
-    FontData(&'a Vec<u8>),
+    /// The bytes of a font file, and which face in it to use if it's a TrueType collection. Azure
+    /// copies the face it needs, so the bytes are dropped once the font has been made.
+    FontData(Vec<u8>, u32),
+    /// A family name, such as "sans-serif", for Skia to look up through fontconfig.
+    FontName(String, FontStyle),
+}
+
+/// A glyph in a font, and where its origin goes.
+#[deriving(Clone, PartialEq)]
+pub struct Glyph {
+    pub index: u32,
+    pub position: Point2D<AzFloat>,
+}
+
+impl Glyph {
+    pub fn new(index: u32, position: Point2D<AzFloat>) -> Glyph {
+        Glyph {
+            index: index,
+            position: position,
+        }
+    }
+
+    pub fn as_azure_glyph(&self) -> AzGlyph {
+        struct__AzGlyph {
+            mIndex: self.index,
+            mPosition: self.position.as_azure_point(),
+        }
+    }
+}
+
+/// Calls `f` with an Azure glyph buffer holding the given glyphs. The buffer is only valid
+/// during the call.
+pub fn with_azure_glyph_buffer<T>(glyphs: &[Glyph], f: |&mut AzGlyphBuffer| -> T) -> T {
+    let mut azure_glyphs: Vec<AzGlyph> = glyphs.iter().map(|glyph| glyph.as_azure_glyph())
+                                                      .collect();
+    let mut glyph_buffer = struct__AzGlyphBuffer {
+        mGlyphs: azure_glyphs.as_mut_ptr(),
+        mNumGlyphs: azure_glyphs.len() as u32,
+    };
+    f(&mut glyph_buffer)
+}
+
+#[deriving(Clone, PartialEq)]
+pub enum FontStyle {
+    NormalFontStyle,
+    ItalicFontStyle,
+    BoldFontStyle,
+    BoldItalicFontStyle,
+}
+
+impl FontStyle {
+    fn as_azure_font_style(self) -> AzFontStyle {
+        self as AzFontStyle
+    }
+}
+
+/// How far glyph outlines are moved to line up with the pixel grid.
+#[deriving(Clone, PartialEq)]
+pub enum FontHinting {
+    NoHinting,
+    LightHinting,
+    NormalHinting,
+    FullHinting,
+}
+
+impl FontHinting {
+    fn as_azure_font_hinting(self) -> AzFontHinting {
+        self as AzFontHinting
+    }
+}
+
+/// Backend-specific settings for how glyphs are rasterized.
+pub struct GlyphRenderingOptions {
+    azure_glyph_rendering_options: AzGlyphRenderingOptionsRef,
+}
+
+impl Drop for GlyphRenderingOptions {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseGlyphRenderingOptions(self.azure_glyph_rendering_options);
+        }
+    }
 }
 
+impl GlyphRenderingOptions {
+    /// Creates options for drawing glyphs to draw targets of the given backend. `subpixel_positioning`
+    /// draws glyphs at fractional positions instead of rounding them to whole pixels. Skia honours
+    /// both settings; CoreGraphics leaves hinting to the system. Returns `None` for other backends,
+    /// or if Azure was built without the backend.
+    pub fn new(backend: BackendType, hinting: FontHinting, subpixel_positioning: bool)
+               -> Option<GlyphRenderingOptions> {
+        let azure_glyph_rendering_options = unsafe {
+            match backend {
+                SkiaBackend => {
+                    AzCreateSkiaGlyphRenderingOptions(hinting.as_azure_font_hinting(),
+                                                      subpixel_positioning)
+                }
+                CoreGraphicsBackend | CoreGraphicsAcceleratedBackend => {
+                    AzCreateCGGlyphRenderingOptions(subpixel_positioning)
+                }
+                _ => ptr::mut_null(),
+            }
+        };
+        if azure_glyph_rendering_options == ptr::mut_null() {
+            return None;
+        }
+        Some(GlyphRenderingOptions {
+            azure_glyph_rendering_options: azure_glyph_rendering_options
+        })
+    }
+
+    pub fn get_ref(&self) -> AzGlyphRenderingOptionsRef {
+        self.azure_glyph_rendering_options
+    }
+}
+
+/// Font-wide metrics, in the same units as glyph positions.
+#[deriving(Clone, PartialEq)]
+pub struct FontMetrics {
+    /// The distance from the baseline up to the top of the tallest glyphs.
+    pub ascent: AzFloat,
+    /// The distance from the baseline down to the bottom of the lowest glyphs.
+    pub descent: AzFloat,
+    /// The extra space the font asks for between lines.
+    pub line_gap: AzFloat,
+}
+
+/// A font at a particular size.
+///
+/// Cloning a `ScaledFont` is cheap: the clones share one Azure font, whose refcount is atomic.
+/// A `ScaledFont` is `Send` and `Share`, so one font can be handed to any number of paint tasks
+/// instead of each task creating its own. Azure never changes a font after creating it, so the
+/// methods here are safe to call from several tasks at once, and any task can draw with it.
+/// Recording draw targets are the exception to that last part: see
+/// `DrawTarget::new_recording`.
 pub struct ScaledFont {
     azure_scaled_font: AzScaledFontRef,
 }
@@ -66,11 +211,122 @@ impl Drop for ScaledFont {
     }
 }
 
+impl Clone for ScaledFont {
+    fn clone(&self) -> ScaledFont {
+        unsafe {
+            AzRetainScaledFont(self.azure_scaled_font);
+        }
+        ScaledFont {
+            azure_scaled_font: self.azure_scaled_font,
+        }
+    }
+}
+
 impl ScaledFont {
     pub fn get_ref(&self) -> AzScaledFontRef {
         self.azure_scaled_font
     }
 
+    pub fn font_metrics(&self) -> FontMetrics {
+        let metrics = unsafe {
+            AzScaledFontGetFontMetrics(self.azure_scaled_font)
+        };
+        FontMetrics {
+            ascent: metrics.mAscent,
+            descent: metrics.mDescent,
+            line_gap: metrics.mLineGap,
+        }
+    }
+
+    /// Returns how far the pen moves after drawing the given glyph. Glyph ids above 0xffff, which
+    /// no TrueType or OpenType font can have, don't move the pen at all.
+    pub fn glyph_advance(&self, glyph_id: u32) -> AzFloat {
+        unsafe {
+            AzScaledFontGetGlyphAdvance(self.azure_scaled_font, glyph_id)
+        }
+    }
+
+    /// Returns the bounds of the given glyph's ink relative to its origin. Like glyph positions,
+    /// y grows downwards, so glyphs sitting on the baseline have a negative top. Glyph ids above
+    /// 0xffff have empty bounds.
+    pub fn glyph_bounds(&self, glyph_id: u32) -> Rect<AzFloat> {
+        let bounds = unsafe {
+            AzScaledFontGetGlyphBounds(self.azure_scaled_font, glyph_id)
+        };
+        Rect(Point2D(bounds.x, bounds.y), Size2D(bounds.width, bounds.height))
+    }
+
+    /// Maps each character of `text` to a glyph through the font's character map, with glyph 0
+    /// standing in for characters the font lacks. There's no shaping, so ligatures and combining
+    /// marks come out as separate glyphs.
+    pub fn glyph_ids(&self, text: &str) -> Vec<u32> {
+        let codepoints: Vec<u32> = text.chars().map(|c| c as u32).collect();
+        let mut glyph_ids = Vec::from_elem(codepoints.len(), 0u32);
+        unsafe {
+            AzScaledFontGetGlyphIndices(self.azure_scaled_font,
+                                        codepoints.as_ptr(),
+                                        codepoints.len() as u32,
+                                        glyph_ids.as_mut_ptr());
+        }
+        glyph_ids
+    }
+
+    /// Returns the bytes of a font file this font can be made again from, with
+    /// `from_truetype_data`, and the index of its face in that file. Fonts looked up by name
+    /// on FreeType have no file data.
+    pub fn font_file_data(&self) -> Option<(Vec<u8>, u32)> {
+        extern "C" fn copy_font_file_data(data: *const u8,
+                                          length: u32,
+                                          index: u32,
+                                          _: AzFloat,
+                                          baton: *mut c_void) {
+            unsafe {
+                let result: &mut Option<(Vec<u8>, u32)> = mem::transmute(baton);
+                slice::raw::buf_as_slice(data, length as uint, |bytes| {
+                    *result = Some((Vec::from_slice(bytes), index));
+                })
+            }
+        }
+
+        let mut result: Option<(Vec<u8>, u32)> = None;
+        unsafe {
+            AzScaledFontGetFontFileData(self.azure_scaled_font,
+                                        copy_font_file_data,
+                                        mem::transmute(&mut result));
+        }
+        result
+    }
+
+    /// Returns the outlines of the given glyphs as a path for use with the given draw target, or
+    /// `None` if this font can't provide outlines for that draw target's backend.
+    pub fn glyph_path(&self, glyphs: &[Glyph], draw_target: &DrawTarget) -> Option<Path> {
+        let azure_path = with_azure_glyph_buffer(glyphs, |glyph_buffer| {
+            unsafe {
+                AzScaledFontGetPathForGlyphs(self.azure_scaled_font,
+                                             glyph_buffer,
+                                             draw_target.azure_draw_target)
+            }
+        });
+        if azure_path == ptr::mut_null() {
+            return None;
+        }
+        Some(Path {
+            azure_path: azure_path
+        })
+    }
+
+    /// Appends the outlines of the given glyphs to the path being built. Returns false, leaving
+    /// the path untouched, if this font can't provide outlines.
+    pub fn append_glyphs_to(&self, glyphs: &[Glyph], path_builder: &PathBuilder) -> bool {
+        with_azure_glyph_buffer(glyphs, |glyph_buffer| {
+            unsafe {
+                AzScaledFontCopyGlyphsToBuilder(self.azure_scaled_font,
+                                                glyph_buffer,
+                                                path_builder.azure_path_builder)
+            }
+        })
+    }
+
     #[cfg(target_os="linux")]
     #[cfg(target_os="android")]
     pub fn new(backend: BackendType, font_info: FontInfo, size: AzFloat)
@@ -86,14 +342,24 @@ impl ScaledFont {
         match backend {
             SkiaBackend => {
                 unsafe {
-                    let options = match font_info {
+                    // The font data has to outlive the options, which only point at it.
+                    let (options, _data) = match font_info {
                         NativeFont(native_font) => {
                             // NOTE: azure style flags and freetype style flags are the same in the lowest 2 bits
                             let style = ((*native_font).style_flags & 3) as u32;
-                            AzCreateFontOptionsForName(&*(*native_font).family_name, style)
+                            (AzCreateFontOptionsForName(&*(*native_font).family_name, style), None)
                         },
-                        FontData(bytes) => {
-                            AzCreateFontOptionsForData(bytes.as_ptr(), bytes.len() as u32)
+                        FontData(bytes, face_index) => {
+                            let options = AzCreateFontOptionsForData(bytes.as_ptr(),
+                                                                     bytes.len() as u32,
+                                                                     face_index);
+                            (options, Some(bytes))
+                        },
+                        FontName(name, style) => {
+                            let options = name.as_slice().to_c_str().with_ref(|name| {
+                                AzCreateFontOptionsForName(name, style.as_azure_font_style())
+                            });
+                            (options, None)
                         },
                     };
                     azure_native_font.mType = AZ_NATIVE_FONT_SKIA_FONT_FACE;
@@ -101,18 +367,46 @@ impl ScaledFont {
                     let azure_native_font_ptr = &mut azure_native_font;
                     let azure_scaled_font = AzCreateScaledFontForNativeFont(azure_native_font_ptr, size);
                     AzDestroyFontOptions(options);
-                    ScaledFont { azure_scaled_font: azure_scaled_font }
+                    ScaledFont {
+                        azure_scaled_font: azure_scaled_font
+                    }
                 }
             }
             _ => { fail!("don't know how to make a scaled font for this backend"); }
         }
     }
 
+    /// Creates a font for the given backend from the bytes of a TrueType or OpenType font file,
+    /// or of a TrueType collection, in which case `face_index` picks the face. Azure keeps its
+    /// own copy of the bytes for as long as the font lives. Returns `None` if the backend can't
+    /// load font data or the data doesn't hold such a face.
+    pub fn from_truetype_data(data: &[u8], face_index: u32, size: AzFloat, backend: BackendType)
+                              -> Option<ScaledFont> {
+        let font_type = match font_type_for_backend(backend) {
+            Some(font_type) => font_type,
+            None => return None,
+        };
+
+        unsafe {
+            let azure_scaled_font = AzCreateScaledFontForTrueTypeData(data.as_ptr(),
+                                                                      data.len() as u32,
+                                                                      face_index,
+                                                                      size,
+                                                                      font_type);
+            if azure_scaled_font.is_null() {
+                None
+            } else {
+                Some(ScaledFont {
+                    azure_scaled_font: azure_scaled_font
+                })
+            }
+        }
+    }
+
     /// Mac-specific function to create a font for the given backend.
     #[cfg(target_os="macos")]
     pub fn new(backend: BackendType, native_font: &CGFont, size: AzFloat) -> ScaledFont {
         use azure::AZ_NATIVE_FONT_MAC_FONT_FACE;
-        use azure_hl::{CoreGraphicsBackend,CoreGraphicsAcceleratedBackend};
         use scaled_font::macos::core_foundation::base::TCFType;
 
         let mut azure_native_font = struct__AzNativeFont {
@@ -141,6 +435,38 @@ impl ScaledFont {
     }
 }
 
+#[cfg(target_os="linux")]
+#[cfg(target_os="android")]
+fn font_type_for_backend(backend: BackendType) -> Option<AzFontType> {
+    use azure::AZ_FONT_SKIA;
+
+    match backend {
+        SkiaBackend => Some(AZ_FONT_SKIA),
+        _ => None,
+    }
+}
+
+#[cfg(target_os="macos")]
+fn font_type_for_backend(backend: BackendType) -> Option<AzFontType> {
+    use azure::AZ_FONT_MAC;
+
+    match backend {
+        CoreGraphicsBackend | CoreGraphicsAcceleratedBackend | SkiaBackend => Some(AZ_FONT_MAC),
+        _ => None,
+    }
+}
+
+#[cfg(target_os="windows")]
+fn font_type_for_backend(backend: BackendType) -> Option<AzFontType> {
+    use azure::AZ_FONT_DWRITE;
+    use azure_hl::Direct2DBackend;
+
+    match backend {
+        Direct2DBackend => Some(AZ_FONT_DWRITE),
+        _ => None,
+    }
+}
+
 // FIXME: Move this stuff to a rust-skia?
 // FIXME: Demangle the names!!!
 #[cfg(target_os="macos")]
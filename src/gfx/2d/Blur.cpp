@@ -334,7 +334,8 @@ AlphaBoxBlur::AlphaBoxBlur(const Rect& aRect,
  : mSpreadRadius(aSpreadRadius),
    mBlurRadius(aBlurRadius),
    mData(nullptr),
-   mFreeData(true)
+   mFreeData(true),
+   mStride(0)
 {
   Rect rect(aRect);
   rect.Inflate(Size(aBlurRadius + aSpreadRadius));
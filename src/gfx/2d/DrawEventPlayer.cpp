@@ -0,0 +1,233 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "DrawEventPlayer.h"
+
+namespace mozilla {
+namespace gfx {
+
+using namespace std;
+
+template<typename T>
+static T*
+LookupInMap(const map<void*, RefPtr<T> > &aMap, ReferencePtr aRefPtr)
+{
+  typename map<void*, RefPtr<T> >::const_iterator iter = aMap.find(aRefPtr);
+  if (iter == aMap.end()) {
+    return NULL;
+  }
+  return iter->second;
+}
+
+DrawEventPlayer::DrawEventPlayer(BackendType aBackendType)
+  : mBackendType(aBackendType)
+  , mError(RECORDING_ERROR_NONE)
+  , mDrawTargetBytes(0)
+{
+  // Only used to create similar draw targets, paths and surfaces, so its size
+  // does not matter.
+  mReferenceDrawTarget =
+    Factory::CreateDrawTarget(aBackendType, IntSize(1, 1), FORMAT_B8G8R8A8);
+}
+
+bool
+DrawEventPlayer::PlayStream(istream &aStream)
+{
+  if (!mReferenceDrawTarget) {
+    return false;
+  }
+
+  mError = RecordedEvent::ReadStreamHeader(aStream);
+  if (mError != RECORDING_ERROR_NONE) {
+    return false;
+  }
+
+  for (;;) {
+    RecordedEvent *event = RecordedEvent::LoadNextEventFromStream(aStream, mError);
+    if (!event) {
+      return mError == RECORDING_ERROR_NONE;
+    }
+
+    bool played = PlayEvent(event);
+    delete event;
+    if (!played) {
+      return false;
+    }
+  }
+}
+
+bool
+DrawEventPlayer::PlayEvent(RecordedEvent *aEvent)
+{
+  if (!mReferenceDrawTarget) {
+    return false;
+  }
+
+  if (aEvent->GetType() == RecordedEvent::DRAWTARGETCREATION) {
+    RecordedDrawTargetCreation *creation = static_cast<RecordedDrawTargetCreation*>(aEvent);
+    uint64_t bytes = RecordedSurfaceBytes(creation->mSize, creation->mFormat);
+    if (bytes > kMaxPlayedDrawTargetBytes - mDrawTargetBytes) {
+      mError = RECORDING_ERROR_INVALID_EVENT;
+      return false;
+    }
+    mDrawTargetBytes += bytes;
+  }
+
+  if (!aEvent->PlayEvent(this)) {
+    mError = RECORDING_ERROR_UNKNOWN_REFERENCE;
+    return false;
+  }
+  return true;
+}
+
+DrawTarget*
+DrawEventPlayer::LookupDrawTarget(ReferencePtr aRefPtr)
+{
+  return LookupInMap(mDrawTargets, aRefPtr);
+}
+
+Path*
+DrawEventPlayer::LookupPath(ReferencePtr aRefPtr)
+{
+  return LookupInMap(mPaths, aRefPtr);
+}
+
+SourceSurface*
+DrawEventPlayer::LookupSourceSurface(ReferencePtr aRefPtr)
+{
+  return LookupInMap(mSourceSurfaces, aRefPtr);
+}
+
+GradientStops*
+DrawEventPlayer::LookupGradientStops(ReferencePtr aRefPtr)
+{
+  return LookupInMap(mGradientStops, aRefPtr);
+}
+
+ScaledFont*
+DrawEventPlayer::LookupScaledFont(ReferencePtr aRefPtr)
+{
+  return LookupInMap(mScaledFonts, aRefPtr);
+}
+
+void
+DrawEventPlayer::AddDrawTarget(ReferencePtr aRefPtr, DrawTarget *aDT)
+{
+  mDrawTargets[aRefPtr] = aDT;
+  if (aDT) {
+    mCreatedDrawTargets.push_back(aDT);
+  }
+}
+
+void
+DrawEventPlayer::RemoveDrawTarget(ReferencePtr aRefPtr)
+{
+  mDrawTargets.erase(aRefPtr);
+}
+
+void
+DrawEventPlayer::AddPath(ReferencePtr aRefPtr, Path *aPath)
+{
+  mPaths[aRefPtr] = aPath;
+}
+
+void
+DrawEventPlayer::RemovePath(ReferencePtr aRefPtr)
+{
+  mPaths.erase(aRefPtr);
+}
+
+void
+DrawEventPlayer::AddSourceSurface(ReferencePtr aRefPtr, SourceSurface *aSurface)
+{
+  mSourceSurfaces[aRefPtr] = aSurface;
+}
+
+void
+DrawEventPlayer::RemoveSourceSurface(ReferencePtr aRefPtr)
+{
+  mSourceSurfaces.erase(aRefPtr);
+}
+
+void
+DrawEventPlayer::AddGradientStops(ReferencePtr aRefPtr, GradientStops *aStops)
+{
+  mGradientStops[aRefPtr] = aStops;
+}
+
+void
+DrawEventPlayer::RemoveGradientStops(ReferencePtr aRefPtr)
+{
+  mGradientStops.erase(aRefPtr);
+}
+
+void
+DrawEventPlayer::AddScaledFont(ReferencePtr aRefPtr, ScaledFont *aScaledFont)
+{
+  mScaledFonts[aRefPtr] = aScaledFont;
+}
+
+void
+DrawEventPlayer::RemoveScaledFont(ReferencePtr aRefPtr)
+{
+  mScaledFonts.erase(aRefPtr);
+}
+
+FontType
+DrawEventPlayer::GetDesiredFontType()
+{
+  switch (mBackendType) {
+  case BACKEND_DIRECT2D:
+    return FONT_DWRITE;
+  case BACKEND_CAIRO:
+    return FONT_CAIRO;
+  case BACKEND_COREGRAPHICS:
+  case BACKEND_COREGRAPHICS_ACCELERATED:
+    return FONT_MAC;
+  default:
+    return FONT_SKIA;
+  }
+}
+
+RecordedEventReader::RecordedEventReader(const string &aRecording)
+  : mStream(aRecording, ios_base::in | ios_base::binary)
+  , mCurrentEvent(NULL)
+{
+  mError = RecordedEvent::ReadStreamHeader(mStream);
+}
+
+RecordedEventReader::~RecordedEventReader()
+{
+  delete mCurrentEvent;
+}
+
+bool
+RecordedEventReader::Next()
+{
+  delete mCurrentEvent;
+  mCurrentEvent = NULL;
+  mCurrentEventInfo.clear();
+
+  if (mError != RECORDING_ERROR_NONE) {
+    return false;
+  }
+
+  mCurrentEvent = RecordedEvent::LoadNextEventFromStream(mStream, mError);
+  return mCurrentEvent != NULL;
+}
+
+const string &
+RecordedEventReader::CurrentEventInfo()
+{
+  if (mCurrentEvent && mCurrentEventInfo.empty()) {
+    stringstream info;
+    mCurrentEvent->OutputSimpleEventInfo(info);
+    mCurrentEventInfo = info.str();
+  }
+  return mCurrentEventInfo;
+}
+
+}
+}
@@ -0,0 +1,126 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#ifndef MOZILLA_GFX_DRAWEVENTPLAYER_H_
+#define MOZILLA_GFX_DRAWEVENTPLAYER_H_
+
+#include "2D.h"
+#include "RecordedEvent.h"
+#include <istream>
+#include <map>
+#include <sstream>
+#include <string>
+#include <vector>
+
+namespace mozilla {
+namespace gfx {
+
+/* A Translator which replays a stream written by a DrawEventRecorder onto
+ * draw targets of a single backend. Every draw target created by the stream
+ * is kept alive after its destruction event, so callers can inspect what was
+ * drawn once playback has finished.
+ */
+class DrawEventPlayer : public Translator
+{
+public:
+  DrawEventPlayer(BackendType aBackendType);
+  virtual ~DrawEventPlayer() { }
+
+  // Plays every event in aStream, which must start with the recording header.
+  // Returns false if this player's backend is not available or the stream
+  // could not be read, GetError() tells why. Events played before the failure
+  // are not undone.
+  bool PlayStream(std::istream &aStream);
+  // Plays a single event, for callers which want to look at the draw targets
+  // part way through a recording. Returns false if this player's backend is
+  // not available or the event refers to an object this player doesn't have,
+  // in which case GetError() returns RECORDING_ERROR_UNKNOWN_REFERENCE, or
+  // would take the draw targets created by this player over
+  // kMaxPlayedDrawTargetBytes, in which case it returns
+  // RECORDING_ERROR_INVALID_EVENT.
+  bool PlayEvent(RecordedEvent *aEvent);
+
+  // Why the last call to PlayStream could not play the whole stream.
+  RecordingError GetError() const { return mError; }
+
+  size_t GetDrawTargetCount() const { return mCreatedDrawTargets.size(); }
+  DrawTarget *GetDrawTarget(size_t aIndex) const { return mCreatedDrawTargets[aIndex]; }
+
+  virtual DrawTarget *LookupDrawTarget(ReferencePtr aRefPtr);
+  virtual Path *LookupPath(ReferencePtr aRefPtr);
+  virtual SourceSurface *LookupSourceSurface(ReferencePtr aRefPtr);
+  virtual GradientStops *LookupGradientStops(ReferencePtr aRefPtr);
+  virtual ScaledFont *LookupScaledFont(ReferencePtr aRefPtr);
+  virtual void AddDrawTarget(ReferencePtr aRefPtr, DrawTarget *aDT);
+  virtual void RemoveDrawTarget(ReferencePtr aRefPtr);
+  virtual void AddPath(ReferencePtr aRefPtr, Path *aPath);
+  virtual void RemovePath(ReferencePtr aRefPtr);
+  virtual void AddSourceSurface(ReferencePtr aRefPtr, SourceSurface *aSurface);
+  virtual void RemoveSourceSurface(ReferencePtr aRefPtr);
+  virtual void AddGradientStops(ReferencePtr aRefPtr, GradientStops *aStops);
+  virtual void RemoveGradientStops(ReferencePtr aRefPtr);
+  virtual void AddScaledFont(ReferencePtr aRefPtr, ScaledFont *aScaledFont);
+  virtual void RemoveScaledFont(ReferencePtr aRefPtr);
+
+  virtual DrawTarget *GetReferenceDrawTarget() { return mReferenceDrawTarget; }
+  virtual FontType GetDesiredFontType();
+
+private:
+  typedef std::map<void*, RefPtr<DrawTarget> > DrawTargetMap;
+  typedef std::map<void*, RefPtr<Path> > PathMap;
+  typedef std::map<void*, RefPtr<SourceSurface> > SourceSurfaceMap;
+  typedef std::map<void*, RefPtr<GradientStops> > GradientStopsMap;
+  typedef std::map<void*, RefPtr<ScaledFont> > ScaledFontMap;
+
+  BackendType mBackendType;
+  RefPtr<DrawTarget> mReferenceDrawTarget;
+  RecordingError mError;
+  // The size of every draw target created so far, see kMaxPlayedDrawTargetBytes.
+  uint64_t mDrawTargetBytes;
+
+  DrawTargetMap mDrawTargets;
+  PathMap mPaths;
+  SourceSurfaceMap mSourceSurfaces;
+  GradientStopsMap mGradientStops;
+  ScaledFontMap mScaledFonts;
+
+  std::vector<RefPtr<DrawTarget> > mCreatedDrawTargets;
+};
+
+/* Reads the events of a recording one at a time without playing them back,
+ * for tools which inspect recordings.
+ */
+class RecordedEventReader
+{
+public:
+  // aRecording is copied, the caller does not need to keep it alive.
+  RecordedEventReader(const std::string &aRecording);
+  ~RecordedEventReader();
+
+  // Whether the recording's header was not understood or reading stopped on
+  // a malformed event rather than at the end of the recording.
+  bool HasFailed() const { return mError != RECORDING_ERROR_NONE; }
+  RecordingError GetError() const { return mError; }
+
+  // Advances to the next event. Returns false at the end of the recording or
+  // when it is malformed.
+  bool Next();
+  // The current event, valid until the next call to Next().
+  RecordedEvent *CurrentEvent() const { return mCurrentEvent; }
+  // The current event as printed by OutputSimpleEventInfo, valid until the
+  // next call to Next().
+  const std::string &CurrentEventInfo();
+
+private:
+  std::istringstream mStream;
+  RecordedEvent *mCurrentEvent;
+  std::string mCurrentEventInfo;
+  RecordingError mError;
+};
+
+}
+}
+
+#endif /* MOZILLA_GFX_DRAWEVENTPLAYER_H_ */
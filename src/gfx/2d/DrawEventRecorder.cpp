@@ -11,8 +11,6 @@ namespace gfx {
 
 using namespace std;
 
-const uint32_t kMagicInt = 0xc001feed;
-
 DrawEventRecorderPrivate::DrawEventRecorderPrivate(std::ostream *aStream)
   : mOutputStream(aStream)
 {
@@ -28,15 +26,38 @@ DrawEventRecorderPrivate::RecordEvent(const RecordedEvent &aEvent)
   Flush();
 }
 
+void
+DrawEventRecorderPrivate::ReleaseUnusedScaledFonts()
+{
+  // Nobody else can get hold of a font again once the recorder has the only
+  // reference, so it can't be drawn with any more.
+  vector<RefPtr<ScaledFont> > usedFonts;
+  for (size_t i = 0; i < mScaledFonts.size(); i++) {
+    if (mScaledFonts[i]->hasOneRef()) {
+      RecordEvent(RecordedScaledFontDestruction(mScaledFonts[i]));
+      mStoredScaledFonts.erase(mScaledFonts[i]);
+    } else {
+      usedFonts.push_back(mScaledFonts[i]);
+    }
+  }
+  mScaledFonts.swap(usedFonts);
+}
+
+void
+DrawEventRecorderPrivate::WriteHeader()
+{
+  WriteElement(*mOutputStream, kMagicInt);
+  WriteElement(*mOutputStream, kMajorRevision);
+  WriteElement(*mOutputStream, kMinorRevision);
+}
+
 DrawEventRecorderFile::DrawEventRecorderFile(const char *aFilename)
   : DrawEventRecorderPrivate(NULL) 
   , mOutputFile(aFilename, ofstream::binary)
 {
   mOutputStream = &mOutputFile;
 
-  WriteElement(*mOutputStream, kMagicInt);
-  WriteElement(*mOutputStream, kMajorRevision);
-  WriteElement(*mOutputStream, kMinorRevision);
+  WriteHeader();
 }
 
 DrawEventRecorderFile::~DrawEventRecorderFile()
@@ -50,5 +71,30 @@ DrawEventRecorderFile::Flush()
   mOutputFile.flush();
 }
 
+DrawEventRecorderMemory::DrawEventRecorderMemory()
+  : DrawEventRecorderPrivate(NULL)
+  , mMemoryStream(ios_base::in | ios_base::out | ios_base::binary)
+{
+  mOutputStream = &mMemoryStream;
+
+  WriteHeader();
+}
+
+void
+DrawEventRecorderMemory::TakeRecording(RecordingOutput aOutput, void *aBaton)
+{
+  ReleaseUnusedScaledFonts();
+
+  string recording = mMemoryStream.str();
+  mMemoryStream.str(string());
+  mMemoryStream.clear();
+  aOutput(reinterpret_cast<const uint8_t*>(recording.data()), recording.size(), aBaton);
+}
+
+void
+DrawEventRecorderMemory::Flush()
+{
+}
+
 }
 }
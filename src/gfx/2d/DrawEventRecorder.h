@@ -10,6 +10,8 @@
 #include "RecordedEvent.h"
 #include <ostream>
 #include <fstream>
+#include <sstream>
+#include <vector>
 
 #if defined(_MSC_VER)
 #include <hash_set>
@@ -46,9 +48,34 @@ public:
     return false;
   }
 
+  /* The recorder keeps track of the fonts it has recorded itself, rather than
+   * marking them with user data, since a font may be in use on other threads.
+   * It also holds on to them, so that a font it hasn't seen can't turn up at
+   * the address of one that has been destroyed, until it finds that it holds
+   * the last reference to them in ReleaseUnusedScaledFonts.
+   */
+  void AddStoredScaledFont(ScaledFont *aFont) {
+    ReleaseUnusedScaledFonts();
+    mStoredScaledFonts.insert(aFont);
+    mScaledFonts.push_back(aFont);
+  }
+
+  bool HasStoredScaledFont(ScaledFont *aFont) {
+    if (mStoredScaledFonts.find(aFont) != mStoredScaledFonts.end()) {
+      return true;
+    }
+    return false;
+  }
+
 protected:
   std::ostream *mOutputStream;
 
+  void WriteHeader();
+  // Records the destruction of the fonts nothing but the recorder still
+  // holds, and releases them. Called whenever a font is added, and by
+  // recorders which hand out their recording before they do so.
+  void ReleaseUnusedScaledFonts();
+
   virtual void Flush() = 0;
 
 #if defined(_MSC_VER)
@@ -59,6 +86,7 @@ protected:
 
   ObjectSet mStoredPaths;
   ObjectSet mStoredScaledFonts;
+  std::vector<RefPtr<ScaledFont> > mScaledFonts;
 };
 
 class DrawEventRecorderFile : public DrawEventRecorderPrivate
@@ -67,12 +95,37 @@ public:
   DrawEventRecorderFile(const char *aFilename);
   ~DrawEventRecorderFile();
 
+  bool IsOpen() { return mOutputFile.is_open(); }
+
 private:
   virtual void Flush();
 
   std::ofstream mOutputFile;
 };
 
+/* Keeps the recording in memory so it can be handed to another process, e.g.
+ * over IPC, instead of going through a file.
+ */
+class DrawEventRecorderMemory : public DrawEventRecorderPrivate
+{
+public:
+  DrawEventRecorderMemory();
+
+  typedef void (*RecordingOutput)(const uint8_t *aData, size_t aLength, void *aBaton);
+
+  /* Passes everything recorded since the last call to aOutput, and empties the
+   * stream so the next call only sees what is recorded after this one. The
+   * first call's data starts with the header, and concatenating the data from
+   * every call gives the complete recording.
+   */
+  void TakeRecording(RecordingOutput aOutput, void *aBaton);
+
+private:
+  virtual void Flush();
+
+  std::stringstream mMemoryStream;
+};
+
 }
 }
 
@@ -736,7 +736,7 @@ DrawTargetCG::Fill(const Path *aPath, const Pattern &aPattern, const DrawOptions
 
 void
 DrawTargetCG::FillGlyphs(ScaledFont *aFont, const GlyphBuffer &aBuffer, const Pattern &aPattern, const DrawOptions &aDrawOptions,
-                         const GlyphRenderingOptions*)
+                         const GlyphRenderingOptions *aRenderingOptions)
 {
   MarkChanged();
 
@@ -748,6 +748,12 @@ DrawTargetCG::FillGlyphs(ScaledFont *aFont, const GlyphBuffer &aBuffer, const Pa
   CGContextRef cg = fixer.Check(mCg, aDrawOptions.mCompositionOp);
   CGContextSetAlpha(cg, aDrawOptions.mAlpha);
 
+  if (aRenderingOptions && aRenderingOptions->GetType() == FONT_MAC) {
+    const GlyphRenderingOptionsCG *options =
+      static_cast<const GlyphRenderingOptionsCG*>(aRenderingOptions);
+    CGContextSetShouldSubpixelPositionFonts(cg, options->GetSubpixelPositioning());
+  }
+
   CGContextConcatCTM(cg, GfxMatrixToCGAffineTransform(mTransform));
 
   ScaledFontMac* macFont = static_cast<ScaledFontMac*>(aFont);
@@ -203,22 +203,6 @@ DrawTargetRecording::Fill(const Path *aPath,
   mFinalDT->Fill(GetPathForPathRecording(aPath), *AdjustedPattern(aPattern), aOptions);
 }
 
-struct RecordingFontUserData
-{
-  void *refPtr;
-  RefPtr<DrawEventRecorderPrivate> recorder;
-};
-
-void RecordingFontUserDataDestroyFunc(void *aUserData)
-{
-  RecordingFontUserData *userData =
-    static_cast<RecordingFontUserData*>(aUserData);
-
-  userData->recorder->RecordEvent(RecordedScaledFontDestruction(userData->refPtr));
-
-  delete userData;
-}
-
 void
 DrawTargetRecording::FillGlyphs(ScaledFont *aFont,
                                 const GlyphBuffer &aBuffer,
@@ -226,13 +210,9 @@ DrawTargetRecording::FillGlyphs(ScaledFont *aFont,
                                 const DrawOptions &aOptions,
                                 const GlyphRenderingOptions *aRenderingOptions)
 {
-  if (!aFont->GetUserData(reinterpret_cast<UserDataKey*>(mRecorder.get()))) {
+  if (!mRecorder->HasStoredScaledFont(aFont)) {
     mRecorder->RecordEvent(RecordedScaledFontCreation(aFont, aFont));
-    RecordingFontUserData *userData = new RecordingFontUserData;
-    userData->refPtr = aFont;
-    userData->recorder = mRecorder;
-    aFont->AddUserData(reinterpret_cast<UserDataKey*>(mRecorder.get()), userData, 
-                       &RecordingFontUserDataDestroyFunc);
+    mRecorder->AddStoredScaledFont(aFont);
   }
 
   mRecorder->RecordEvent(RecordedFillGlyphs(this, aFont, aPattern, aOptions, aBuffer.mGlyphs, aBuffer.mNumGlyphs));
@@ -22,8 +22,10 @@
 #include "SkDashPathEffect.h"
 #include "Logging.h"
 #include "HelpersSkia.h"
+#include "Scale.h"
 #include "Tools.h"
 #include <algorithm>
+#include <math.h>
 
 #ifdef ANDROID
 # define USE_SOFT_CLIPPING false
@@ -256,6 +258,75 @@ DrawTargetSkia::Flush()
   mCanvas->flush();
 }
 
+// Below this scale, FILTER_GOOD resamples the source before drawing it, since
+// bilinear filtering would skip over most of the source pixels.
+static const Float kPrescaleThreshold = 0.5f;
+
+/* If aSource is drawn into aDest under aTransform at less than
+ * kPrescaleThreshold of its size, resamples that part of aBitmap to the size it
+ * will appear at and returns true, with aScaledBitmap and aScaledSource set to
+ * the result and the part of it to draw instead. */
+static bool
+PrescaleBitmap(const SkBitmap &aBitmap, const Rect &aDest, const Rect &aSource,
+               const Matrix &aTransform, SkBitmap &aScaledBitmap, Rect &aScaledSource)
+{
+  // The resampled pixels only line up with the device pixels if the transform
+  // is a scale and translation.
+  if (aTransform._12 != 0 || aTransform._21 != 0 ||
+      aBitmap.config() != SkBitmap::kARGB_8888_Config) {
+    return false;
+  }
+
+  Float scaleX = fabs(aTransform._11 * aDest.width / aSource.width);
+  Float scaleY = fabs(aTransform._22 * aDest.height / aSource.height);
+  if (scaleX >= kPrescaleThreshold && scaleY >= kPrescaleThreshold) {
+    return false;
+  }
+
+  Rect sourceRect = aSource.Intersect(Rect(0, 0, aBitmap.width(), aBitmap.height()));
+  sourceRect.RoundOut();
+  if (sourceRect.IsEmpty()) {
+    return false;
+  }
+
+  SkBitmap subset;
+  SkIRect subsetRect = SkIRect::MakeXYWH(int32_t(sourceRect.x), int32_t(sourceRect.y),
+                                         int32_t(sourceRect.width), int32_t(sourceRect.height));
+  if (!aBitmap.extractSubset(&subset, subsetRect)) {
+    return false;
+  }
+
+  // Never enlarge along the axis which isn't scaled down.
+  int32_t width = std::max(int32_t(ceil(sourceRect.width * std::min(scaleX, Float(1)))), 1);
+  int32_t height = std::max(int32_t(ceil(sourceRect.height * std::min(scaleY, Float(1)))), 1);
+  aScaledBitmap.setConfig(SkBitmap::kARGB_8888_Config, width, height);
+  if (!aScaledBitmap.allocPixels()) {
+    return false;
+  }
+
+  SkAutoLockPixels subsetLock(subset);
+  SkAutoLockPixels scaledLock(aScaledBitmap);
+  // Bitmaps backed by textures have no pixels to read.
+  if (!subset.getPixels()) {
+    return false;
+  }
+  SurfaceFormat format = aBitmap.isOpaque() ? FORMAT_B8G8R8X8 : FORMAT_B8G8R8A8;
+  if (!Scale(static_cast<uint8_t*>(subset.getPixels()),
+             subset.width(), subset.height(), int32_t(subset.rowBytes()),
+             static_cast<uint8_t*>(aScaledBitmap.getPixels()),
+             width, height, int32_t(aScaledBitmap.rowBytes()),
+             format, RESIZE_BETTER)) {
+    return false;
+  }
+  aScaledBitmap.setIsOpaque(aBitmap.isOpaque());
+
+  Float ratioX = width / sourceRect.width;
+  Float ratioY = height / sourceRect.height;
+  aScaledSource = Rect((aSource.x - sourceRect.x) * ratioX, (aSource.y - sourceRect.y) * ratioY,
+                       aSource.width * ratioX, aSource.height * ratioY);
+  return true;
+}
+
 void
 DrawTargetSkia::DrawSurface(SourceSurface *aSurface,
                             const Rect &aDest,
@@ -273,19 +344,28 @@ DrawTargetSkia::DrawSurface(SourceSurface *aSurface,
 
   MarkChanged();
 
+  const SkBitmap& bitmap = static_cast<SourceSurfaceSkia*>(aSurface)->GetBitmap();
+
+  // Only draw targets in memory can afford to resample for FILTER_GOOD; the
+  // GPU backend has no bitmap of its own.
+  SkBitmap scaledBitmap;
+  Rect source = aSource;
+  bool prescaled = aSurfOptions.mFilter == FILTER_GOOD && !mBitmap.isNull() &&
+                   PrescaleBitmap(bitmap, aDest, aSource, mTransform, scaledBitmap, source);
+
   SkRect destRect = RectToSkRect(aDest);
-  SkRect sourceRect = RectToSkRect(aSource);
+  SkRect sourceRect = RectToSkRect(source);
 
   SkMatrix matrix;
   matrix.setRectToRect(sourceRect, destRect, SkMatrix::kFill_ScaleToFit);
-  
-  const SkBitmap& bitmap = static_cast<SourceSurfaceSkia*>(aSurface)->GetBitmap();
  
   AutoPaintSetup paint(mCanvas.get(), aOptions);
-  SkShader *shader = SkShader::CreateBitmapShader(bitmap, SkShader::kClamp_TileMode, SkShader::kClamp_TileMode);
+  SkShader *shader = SkShader::CreateBitmapShader(prescaled ? scaledBitmap : bitmap,
+                                                  SkShader::kClamp_TileMode,
+                                                  SkShader::kClamp_TileMode);
   shader->setLocalMatrix(matrix);
   SkSafeUnref(paint.mPaint.setShader(shader));
-  if (aSurfOptions.mFilter != FILTER_LINEAR) {
+  if (aSurfOptions.mFilter == FILTER_POINT) {
     paint.mPaint.setFilterBitmap(false);
   }
   mCanvas->drawRect(destRect, paint.mPaint);
@@ -445,7 +525,7 @@ DrawTargetSkia::FillGlyphs(ScaledFont *aFont,
                            const GlyphBuffer &aBuffer,
                            const Pattern &aPattern,
                            const DrawOptions &aOptions,
-                           const GlyphRenderingOptions*)
+                           const GlyphRenderingOptions *aRenderingOptions)
 {
   if (aFont->GetType() != FONT_MAC &&
       aFont->GetType() != FONT_SKIA &&
@@ -461,7 +541,14 @@ DrawTargetSkia::FillGlyphs(ScaledFont *aFont,
   paint.mPaint.setTypeface(skiaFont->GetSkTypeface());
   paint.mPaint.setTextSize(SkFloatToScalar(skiaFont->mSize));
   paint.mPaint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
-  
+
+  if (aRenderingOptions && aRenderingOptions->GetType() == FONT_SKIA) {
+    const GlyphRenderingOptionsSkia *options =
+      static_cast<const GlyphRenderingOptionsSkia*>(aRenderingOptions);
+    paint.mPaint.setHinting(FontHintingToSkiaHinting(options->GetHinting()));
+    paint.mPaint.setSubpixelText(options->GetSubpixelPositioning());
+  }
+
   std::vector<uint16_t> indices;
   std::vector<SkPoint> offsets;
   indices.resize(aBuffer.mNumGlyphs);
@@ -5,6 +5,8 @@
 
 #include "2D.h"
 
+#include "mozilla/CheckedInt.h"
+
 #ifdef USE_CAIRO
 #include "DrawTargetCairo.h"
 #include "ScaledFontBase.h"
@@ -24,6 +26,8 @@
 
 #ifdef XP_MACOSX
 #include "ScaledFontMac.h"
+#include "SFNTData.h"
+#include <vector>
 #endif
 
 
@@ -41,6 +45,7 @@
 #include "DrawTargetRecording.h"
 
 #include "SourceSurfaceRawData.h"
+#include "Tools.h"
 
 #include "DrawEventRecorder.h"
 
@@ -330,6 +335,41 @@ Factory::CreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize,
     {
       return new ScaledFontDWrite(aData, aSize, aFaceIndex, aGlyphSize);
     }
+#endif
+#if defined(USE_SKIA) && defined(MOZ_ENABLE_FREETYPE)
+  case FONT_SKIA:
+    {
+      RefPtr<ScaledFontFreetype> font =
+        new ScaledFontFreetype(aData, aSize, aFaceIndex, aGlyphSize);
+      if (!font->GetSkTypeface()) {
+        return nullptr;
+      }
+      return font;
+    }
+#endif
+#ifdef XP_MACOSX
+  case FONT_MAC:
+    {
+      // CoreGraphics only loads the first face of a collection too.
+      std::vector<uint8_t> face;
+      if (!CopySFNTFace(aData, aSize, aFaceIndex, face)) {
+        gfxWarning() << "Unable to find face " << aFaceIndex << " in truetype data";
+        return nullptr;
+      }
+
+      CFDataRef data = CFDataCreate(kCFAllocatorDefault, &face.front(), face.size());
+      CGDataProviderRef provider = CGDataProviderCreateWithCFData(data);
+      CGFontRef cgFont = CGFontCreateWithDataProvider(provider);
+      CGDataProviderRelease(provider);
+      CFRelease(data);
+      if (!cgFont) {
+        return nullptr;
+      }
+
+      RefPtr<ScaledFont> font = new ScaledFontMac(cgFont, aGlyphSize);
+      CGFontRelease(cgFont);
+      return font;
+    }
 #endif
   default:
     gfxWarning() << "Unable to create requested font type from truetype data";
@@ -337,6 +377,28 @@ Factory::CreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize,
   }
 }
 
+#ifdef USE_SKIA
+TemporaryRef<GlyphRenderingOptions>
+Factory::CreateSkiaGlyphRenderingOptions(FontHinting aHinting, bool aSubpixelPositioning)
+{
+  RefPtr<GlyphRenderingOptions> options =
+    new GlyphRenderingOptionsSkia(aHinting, aSubpixelPositioning);
+
+  return options;
+}
+#endif
+
+#ifdef XP_MACOSX
+TemporaryRef<GlyphRenderingOptions>
+Factory::CreateCGGlyphRenderingOptions(bool aSubpixelPositioning)
+{
+  RefPtr<GlyphRenderingOptions> options =
+    new GlyphRenderingOptionsCG(aSubpixelPositioning);
+
+  return options;
+}
+#endif
+
 TemporaryRef<ScaledFont>
 Factory::CreateScaledFontWithCairo(const NativeFont& aNativeFont, Float aSize, cairo_scaled_font_t* aScaledFont)
 {
@@ -479,6 +541,33 @@ Factory::CreateDrawTargetForCairoSurface(cairo_surface_t* aSurface, const IntSiz
   return retVal;
 }
 
+TemporaryRef<DataSourceSurface>
+Factory::CreateDataSourceSurface(const IntSize &aSize, SurfaceFormat aFormat)
+{
+  if (aSize.width < 0 || aSize.height < 0) {
+    gfxDebug() << "Invalid size for DataSourceSurface: " << aSize;
+    return nullptr;
+  }
+
+  CheckedInt<int32_t> stride = CheckedInt<int32_t>(aSize.width) * BytesPerPixel(aFormat);
+  CheckedInt<int32_t> size = stride * aSize.height;
+  if (!size.isValid()) {
+    gfxDebug() << "DataSourceSurface is too large: " << aSize;
+    return nullptr;
+  }
+
+  uint8_t *data = new uint8_t[size.value()]();
+
+  RefPtr<SourceSurfaceRawData> newSurf = new SourceSurfaceRawData();
+
+  if (newSurf->InitWrappingData(data, aSize, stride.value(), aFormat, true)) {
+    return newSurf;
+  }
+
+  delete [] data;
+  return nullptr;
+}
+
 TemporaryRef<DataSourceSurface>
 Factory::CreateWrappingDataSourceSurface(uint8_t *aData, int32_t aStride,
                                          const IntSize &aSize,
@@ -502,6 +591,14 @@ Factory::CreateEventRecorderForFile(const char *aFilename)
 void
 Factory::SetGlobalEventRecorder(DrawEventRecorder *aRecorder)
 {
+  // The factory keeps the recorder alive until it is replaced, so callers
+  // don't need to hold on to it themselves.
+  if (aRecorder) {
+    aRecorder->AddRef();
+  }
+  if (mRecorder) {
+    mRecorder->Release();
+  }
   mRecorder = aRecorder;
 }
 
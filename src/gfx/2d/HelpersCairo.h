@@ -86,6 +86,8 @@ GfxFilterToCairoFilter(Filter filter)
       return CAIRO_FILTER_BILINEAR;
     case FILTER_POINT:
       return CAIRO_FILTER_NEAREST;
+    case FILTER_GOOD:
+      return CAIRO_FILTER_GOOD;
   }
 
   return CAIRO_FILTER_BILINEAR;
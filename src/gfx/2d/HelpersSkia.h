@@ -83,6 +83,23 @@ CapStyleToSkiaCap(CapStyle aCap)
   return SkPaint::kDefault_Cap;
 }
 
+static inline SkPaint::Hinting
+FontHintingToSkiaHinting(FontHinting aHinting)
+{
+  switch (aHinting)
+  {
+    case FONT_HINTING_NONE:
+      return SkPaint::kNo_Hinting;
+    case FONT_HINTING_LIGHT:
+      return SkPaint::kSlight_Hinting;
+    case FONT_HINTING_NORMAL:
+      return SkPaint::kNormal_Hinting;
+    case FONT_HINTING_FULL:
+      return SkPaint::kFull_Hinting;
+  }
+  return SkPaint::kNormal_Hinting;
+}
+
 static inline SkPaint::Join
 JoinStyleToSkiaJoin(JoinStyle aJoin)
 {
@@ -46,6 +46,11 @@ inline uint32_t Avg2(uint32_t a, uint32_t b)
 void
 ImageHalfScaler::ScaleForSize(const IntSize &aSize)
 {
+  // Halving bottoms out at a size of zero, so a negative size would never stop.
+  if (aSize.width <= 0 || aSize.height <= 0) {
+    return;
+  }
+
   uint32_t horizontalDownscales = 0;
   uint32_t verticalDownscales = 0;
 
@@ -19,7 +19,7 @@ class ImageHalfScaler
 public:
   ImageHalfScaler(uint8_t *aData, int32_t aStride, const IntSize &aSize)
     : mOrigData(aData), mOrigStride(aStride), mOrigSize(aSize)
-    , mDataStorage(nullptr)
+    , mDataStorage(nullptr), mData(nullptr), mStride(0)
   {
   }
 
@@ -28,6 +28,9 @@ public:
     delete [] mDataStorage;
   }
 
+  // Halves the image, in each direction separately, as many times as it can
+  // while staying larger than aSize. If it can't halve it at all, or aSize is
+  // empty, the scaled data stays null.
   void ScaleForSize(const IntSize &aSize);
 
   uint8_t *GetScaledData() const { return mData; }
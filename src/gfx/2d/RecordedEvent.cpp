@@ -7,12 +7,120 @@
 #include "PathRecording.h"
 
 #include "Tools.h"
+#include "mozilla/CheckedInt.h"
+
+#include <algorithm>
+#include <vector>
 
 namespace mozilla {
 namespace gfx {
 
 using namespace std;
 
+// Lengths and sizes read from a recording can't be trusted. Before anything
+// is allocated for them, the helpers below check that the allocation size
+// does not overflow and, if the stream can seek, that it still holds that
+// much data. Streams which can't seek are not checked up front, so the data
+// is always read in bounded chunks, which keeps memory use in line with the
+// data that is actually there. A failed check puts the stream in a failed
+// state, which makes LoadNextEventFromStream reject the event: with eofbit set
+// it is reported as truncated, otherwise as invalid.
+
+// The most memory ReadElements and ReadBytes allocate before reading into it.
+static const size_t kReadChunkBytes = 64 * 1024;
+
+static void
+RejectEvent(istream &aStream)
+{
+  aStream.setstate(ios_base::failbit);
+}
+
+static bool
+CheckBytesAvailable(istream &aStream, const CheckedInt<size_t> &aBytes)
+{
+  if (!aStream) {
+    return false;
+  }
+  if (!aBytes.isValid()) {
+    RejectEvent(aStream);
+    return false;
+  }
+
+  streampos position = aStream.tellg();
+  if (position == streampos(-1)) {
+    // Not seekable, so the chunked reads will have to run out of data.
+    return true;
+  }
+  aStream.seekg(0, ios_base::end);
+  streampos end = aStream.tellg();
+  aStream.seekg(position);
+  if (uint64_t(end - position) < aBytes.value()) {
+    aStream.setstate(ios_base::eofbit | ios_base::failbit);
+    return false;
+  }
+  return true;
+}
+
+// aElementSize is the size of an element in the stream, which is not always
+// its size in memory.
+static bool
+CheckElementsAvailable(istream &aStream, uint64_t aCount, size_t aElementSize)
+{
+  return CheckBytesAvailable(aStream, CheckedInt<size_t>(aCount) * aElementSize);
+}
+
+// Reads aCount elements into aElements. Fails the stream rather than
+// allocating more than kReadChunkBytes ahead of the data read so far.
+template<typename T>
+static void
+ReadElements(istream &aStream, uint64_t aCount, vector<T> &aElements)
+{
+  const uint64_t chunkLength = kReadChunkBytes / sizeof(T);
+  aElements.clear();
+  while (aStream && aElements.size() < aCount) {
+    size_t start = aElements.size();
+    aElements.resize(start + size_t(min(aCount - start, chunkLength)));
+    for (size_t i = start; i < aElements.size(); i++) {
+      ReadElement(aStream, aElements[i]);
+    }
+  }
+}
+
+// Like ReadElements, for raw bytes.
+static void
+ReadBytes(istream &aStream, uint64_t aLength, vector<uint8_t> &aBytes)
+{
+  aBytes.clear();
+  while (aStream && aBytes.size() < aLength) {
+    size_t start = aBytes.size();
+    size_t length = size_t(min(aLength - start, uint64_t(kReadChunkBytes)));
+    aBytes.resize(start + length);
+    aStream.read((char*)&aBytes[start], length);
+  }
+}
+
+uint64_t
+RecordedSurfaceBytes(const IntSize &aSize, SurfaceFormat aFormat)
+{
+  return uint64_t(aSize.width) * uint64_t(aSize.height) * BytesPerPixel(aFormat);
+}
+
+// Surfaces must have a known format, no negative dimensions and may not be
+// larger than kMaxRecordedSurfaceSize or kMaxRecordedSurfaceBytes. Draw target
+// creation events carry no data, so without the limits a few bytes of
+// recording could make the player allocate gigabytes.
+static bool
+IsValidSurface(const IntSize &aSize, SurfaceFormat aFormat)
+{
+  if (aFormat < FORMAT_B8G8R8A8 || aFormat > FORMAT_A8 ||
+      aSize.width < 0 || aSize.height < 0 ||
+      aSize.width > kMaxRecordedSurfaceSize ||
+      aSize.height > kMaxRecordedSurfaceSize) {
+    return false;
+  }
+  return RecordedSurfaceBytes(aSize, aFormat) <= kMaxRecordedSurfaceBytes;
+}
+
 static std::string NameFromBackend(BackendType aType)
 {
   switch (aType) {
@@ -63,6 +171,51 @@ RecordedEvent::LoadEventFromStream(std::istream &aStream, EventType aType)
   }
 }
 
+RecordingError
+RecordedEvent::ReadStreamHeader(std::istream &aStream)
+{
+  uint32_t magicInt;
+  uint16_t majorRevision;
+  uint16_t minorRevision;
+  ReadElement(aStream, magicInt);
+  ReadElement(aStream, majorRevision);
+  ReadElement(aStream, minorRevision);
+
+  if (!aStream || magicInt != kMagicInt) {
+    return RECORDING_ERROR_BAD_HEADER;
+  }
+  if (majorRevision != kMajorRevision || minorRevision > kMinorRevision) {
+    return RECORDING_ERROR_UNSUPPORTED_VERSION;
+  }
+  return RECORDING_ERROR_NONE;
+}
+
+RecordedEvent *
+RecordedEvent::LoadNextEventFromStream(std::istream &aStream, RecordingError &aError)
+{
+  int32_t type;
+  ReadElement(aStream, type);
+  if (!aStream) {
+    // Running out of data is only fine between events.
+    aError = aStream.eof() && aStream.gcount() == 0 ?
+      RECORDING_ERROR_NONE : RECORDING_ERROR_TRUNCATED;
+    return NULL;
+  }
+
+  RecordedEvent *event = LoadEventFromStream(aStream, EventType(type));
+  if (!event) {
+    aError = RECORDING_ERROR_UNKNOWN_EVENT;
+    return NULL;
+  }
+  if (!aStream) {
+    aError = aStream.eof() ? RECORDING_ERROR_TRUNCATED : RECORDING_ERROR_INVALID_EVENT;
+    delete event;
+    return NULL;
+  }
+  aError = RECORDING_ERROR_NONE;
+  return event;
+}
+
 void
 RecordedEvent::RecordPatternData(std::ostream &aStream, const PatternStorage &aPattern) const
 {
@@ -121,6 +274,7 @@ RecordedEvent::ReadPatternData(std::istream &aStream, PatternStorage &aPattern)
       return;
     }
   default:
+    RejectEvent(aStream);
     return;
   }
 }
@@ -195,7 +349,9 @@ RecordedEvent::RecordStrokeOptions(std::ostream &aStream, const StrokeOptions &a
     return;
   }
 
-  aStream.write((char*)aStrokeOptions.mDashPattern, sizeof(Float) * aStrokeOptions.mDashLength);
+  for (size_t i = 0; i < aStrokeOptions.mDashLength; i++) {
+    WriteElement(aStream, aStrokeOptions.mDashPattern[i]);
+  }
 }
 
 void
@@ -221,9 +377,17 @@ RecordedEvent::ReadStrokeOptions(std::istream &aStream, StrokeOptions &aStrokeOp
     return;
   }
 
-  mDashPatternStorage.resize(aStrokeOptions.mDashLength);
+  if (!CheckElementsAvailable(aStream, dashLength, sizeof(Float))) {
+    aStrokeOptions.mDashLength = 0;
+    return;
+  }
+
+  ReadElements(aStream, dashLength, mDashPatternStorage);
+  if (!aStream) {
+    aStrokeOptions.mDashLength = 0;
+    return;
+  }
   aStrokeOptions.mDashPattern = &mDashPatternStorage.front();
-  aStream.read((char*)aStrokeOptions.mDashPattern, sizeof(Float) * aStrokeOptions.mDashLength);
 }
 
 void
@@ -263,6 +427,28 @@ RecordedEvent::OutputSimplePatternInfo(const PatternStorage &aStorage, std::stri
   }
 }
 
+void
+RecordedEvent::GetDescription(RecordedEventDescription &aDescription)
+{
+  memset(&aDescription, 0, sizeof(aDescription));
+  aDescription.mType = mType;
+  aDescription.mPatternType = -1;
+  aDescription.mMaskPatternType = -1;
+  aDescription.mObject = GetObject().mLongPtr;
+  aDescription.mDestinedDT = GetDestinedDT().mLongPtr;
+
+  Describe(aDescription);
+}
+
+void
+RecordedEvent::DescribePattern(const PatternStorage &aStorage, int32_t &aPatternType, Color &aColor) const
+{
+  aPatternType = aStorage.mType;
+  if (aStorage.mType == PATTERN_COLOR) {
+    aColor = reinterpret_cast<const ColorPatternStorage*>(&aStorage.mStorage)->mColor;
+  }
+}
+
 RecordedDrawingEvent::RecordedDrawingEvent(EventType aType, std::istream &aStream)
   : RecordedEvent(aType)
 {
@@ -281,12 +467,13 @@ RecordedDrawingEvent::GetObject() const
   return mDT;
 }
 
-void
+bool
 RecordedDrawTargetCreation::PlayEvent(Translator *aTranslator) const
 {
   RefPtr<DrawTarget> newDT =
     aTranslator->GetReferenceDrawTarget()->CreateSimilarDrawTarget(mSize, mFormat);
   aTranslator->AddDrawTarget(mRefPtr, newDT);
+  return true;
 }
 
 void
@@ -305,6 +492,10 @@ RecordedDrawTargetCreation::RecordedDrawTargetCreation(istream &aStream)
   ReadElement(aStream, mBackendType);
   ReadElement(aStream, mSize);
   ReadElement(aStream, mFormat);
+  if (mBackendType < BACKEND_NONE || mBackendType > BACKEND_RECORDING ||
+      !IsValidSurface(mSize, mFormat)) {
+    RejectEvent(aStream);
+  }
 }
 
 void
@@ -313,11 +504,20 @@ RecordedDrawTargetCreation::OutputSimpleEventInfo(stringstream &aStringStream) c
   aStringStream << "[" << mRefPtr << "] DrawTarget Creation (Type: " << NameFromBackend(mBackendType) << ", Size: " << mSize.width << "x" << mSize.height << ")";
 }
 
-
 void
+RecordedDrawTargetCreation::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mBackendType = mBackendType;
+  aDescription.mSize = mSize;
+  aDescription.mFormat = mFormat;
+}
+
+
+bool
 RecordedDrawTargetDestruction::PlayEvent(Translator *aTranslator) const
 {
   aTranslator->RemoveDrawTarget(mRefPtr);
+  return true;
 }
 
 void
@@ -338,6 +538,26 @@ RecordedDrawTargetDestruction::OutputSimpleEventInfo(stringstream &aStringStream
   aStringStream << "[" << mRefPtr << "] DrawTarget Destruction";
 }
 
+// Whether the source surface or gradient stops aStorage refers to, if any,
+// exist in aTranslator.
+static bool
+PatternReferencesExist(const PatternStorage &aStorage, Translator *aTranslator)
+{
+  switch (aStorage.mType) {
+  case PATTERN_SURFACE:
+    return aTranslator->LookupSourceSurface(
+      reinterpret_cast<const SurfacePatternStorage*>(&aStorage.mStorage)->mSurface) != NULL;
+  case PATTERN_LINEAR_GRADIENT:
+    return aTranslator->LookupGradientStops(
+      reinterpret_cast<const LinearGradientPatternStorage*>(&aStorage.mStorage)->mStops) != NULL;
+  case PATTERN_RADIAL_GRADIENT:
+    return aTranslator->LookupGradientStops(
+      reinterpret_cast<const RadialGradientPatternStorage*>(&aStorage.mStorage)->mStops) != NULL;
+  default:
+    return true;
+  }
+}
+
 struct GenericPattern
 {
   GenericPattern(const PatternStorage &aStorage, Translator *aTranslator)
@@ -403,10 +623,16 @@ struct GenericPattern
   Translator *mTranslator;
 };
 
-void
+bool
 RecordedFillRect::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->FillRect(mRect, *GenericPattern(mPattern, aTranslator), mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
+  dt->FillRect(mRect, *GenericPattern(mPattern, aTranslator), mOptions);
+  return true;
 }
 
 void
@@ -434,9 +660,22 @@ RecordedFillRect::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedFillRect::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mRect = mRect;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
+bool
 RecordedStrokeRect::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->StrokeRect(mRect, *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
+  dt->StrokeRect(mRect, *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  return true;
 }
 
 void
@@ -467,9 +706,23 @@ RecordedStrokeRect::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedStrokeRect::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mRect = mRect;
+  aDescription.mValue = mStrokeOptions.mLineWidth;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
+bool
 RecordedStrokeLine::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->StrokeLine(mBegin, mEnd, *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
+  dt->StrokeLine(mBegin, mEnd, *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  return true;
 }
 
 void
@@ -502,9 +755,25 @@ RecordedStrokeLine::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedStrokeLine::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mPoint1 = mBegin;
+  aDescription.mPoint2 = mEnd;
+  aDescription.mValue = mStrokeOptions.mLineWidth;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
+bool
 RecordedFill::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->Fill(aTranslator->LookupPath(mPath), *GenericPattern(mPattern, aTranslator), mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  Path *path = aTranslator->LookupPath(mPath);
+  if (!dt || !path ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
+  dt->Fill(path, *GenericPattern(mPattern, aTranslator), mOptions);
+  return true;
 }
 
 RecordedFill::RecordedFill(istream &aStream)
@@ -531,29 +800,51 @@ RecordedFill::OutputSimpleEventInfo(stringstream &aStringStream) const
   OutputSimplePatternInfo(mPattern, aStringStream);
 }
 
+void
+RecordedFill::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mPath.mLongPtr;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
 RecordedFillGlyphs::~RecordedFillGlyphs()
 {
   delete [] mGlyphs;
 }
 
-void
+bool
 RecordedFillGlyphs::PlayEvent(Translator *aTranslator) const
 {
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  ScaledFont *scaledFont = aTranslator->LookupScaledFont(mScaledFont);
+  if (!dt || !scaledFont ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
   GlyphBuffer buffer;
   buffer.mGlyphs = mGlyphs;
   buffer.mNumGlyphs = mNumGlyphs;
-  aTranslator->LookupDrawTarget(mDT)->FillGlyphs(aTranslator->LookupScaledFont(mScaledFont), buffer, *GenericPattern(mPattern, aTranslator), mOptions);
+  dt->FillGlyphs(scaledFont, buffer, *GenericPattern(mPattern, aTranslator), mOptions);
+  return true;
 }
 
 RecordedFillGlyphs::RecordedFillGlyphs(istream &aStream)
-  : RecordedDrawingEvent(FILLGLYPHS, aStream)
+  : RecordedDrawingEvent(FILLGLYPHS, aStream), mGlyphs(NULL)
 {
   ReadElement(aStream, mScaledFont);
   ReadElement(aStream, mOptions);
   ReadPatternData(aStream, mPattern);
   ReadElement(aStream, mNumGlyphs);
+  if (!CheckElementsAvailable(aStream, mNumGlyphs, sizeof(uint32_t) + 2 * sizeof(Float))) {
+    return;
+  }
+  vector<Glyph> glyphs;
+  ReadElements(aStream, mNumGlyphs, glyphs);
+  if (!aStream) {
+    return;
+  }
   mGlyphs = new Glyph[mNumGlyphs];
-  aStream.read((char*)mGlyphs, sizeof(Glyph) * mNumGlyphs);
+  copy(glyphs.begin(), glyphs.end(), mGlyphs);
 }
 
 void
@@ -564,7 +855,9 @@ RecordedFillGlyphs::RecordToStream(ostream &aStream) const
   WriteElement(aStream, mOptions);
   RecordPatternData(aStream, mPattern);
   WriteElement(aStream, mNumGlyphs);
-  aStream.write((char*)mGlyphs, sizeof(Glyph) * mNumGlyphs);
+  for (uint32_t i = 0; i < mNumGlyphs; i++) {
+    WriteElement(aStream, mGlyphs[i]);
+  }
 }
 
 void
@@ -575,9 +868,24 @@ RecordedFillGlyphs::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedFillGlyphs::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mScaledFont.mLongPtr;
+  aDescription.mCount = mNumGlyphs;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
+bool
 RecordedMask::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->Mask(*GenericPattern(mSource, aTranslator), *GenericPattern(mMask, aTranslator), mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt ||
+      !PatternReferencesExist(mSource, aTranslator) ||
+      !PatternReferencesExist(mMask, aTranslator)) {
+    return false;
+  }
+  dt->Mask(*GenericPattern(mSource, aTranslator), *GenericPattern(mMask, aTranslator), mOptions);
+  return true;
 }
 
 RecordedMask::RecordedMask(istream &aStream)
@@ -607,9 +915,23 @@ RecordedMask::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedMask::Describe(RecordedEventDescription &aDescription) const
+{
+  DescribePattern(mSource, aDescription.mPatternType, aDescription.mColor);
+  DescribePattern(mMask, aDescription.mMaskPatternType, aDescription.mMaskColor);
+}
+
+bool
 RecordedStroke::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->Stroke(aTranslator->LookupPath(mPath), *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  Path *path = aTranslator->LookupPath(mPath);
+  if (!dt || !path ||
+      !PatternReferencesExist(mPattern, aTranslator)) {
+    return false;
+  }
+  dt->Stroke(path, *GenericPattern(mPattern, aTranslator), mStrokeOptions, mOptions);
+  return true;
 }
 
 void
@@ -639,9 +961,22 @@ RecordedStroke::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedStroke::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mPath.mLongPtr;
+  aDescription.mValue = mStrokeOptions.mLineWidth;
+  DescribePattern(mPattern, aDescription.mPatternType, aDescription.mColor);
+}
+
+bool
 RecordedClearRect::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->ClearRect(mRect);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt) {
+    return false;
+  }
+  dt->ClearRect(mRect);
+  return true;
 }
 
 void
@@ -664,10 +999,21 @@ RecordedClearRect::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedClearRect::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mRect = mRect;
+}
+
+bool
 RecordedCopySurface::PlayEvent(Translator *aTranslator) const
 {
-	aTranslator->LookupDrawTarget(mDT)->CopySurface(aTranslator->LookupSourceSurface(mSourceSurface),
-                                                  mSourceRect, mDest);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  SourceSurface *surface = aTranslator->LookupSourceSurface(mSourceSurface);
+  if (!dt || !surface) {
+    return false;
+  }
+  dt->CopySurface(surface, mSourceRect, mDest);
+  return true;
 }
 
 void
@@ -694,9 +1040,23 @@ RecordedCopySurface::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedCopySurface::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mSourceSurface.mLongPtr;
+  aDescription.mSourceRect = Rect(mSourceRect.x, mSourceRect.y, mSourceRect.width, mSourceRect.height);
+  aDescription.mPoint1 = Point(mDest.x, mDest.y);
+}
+
+bool
 RecordedPushClip::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->PushClip(aTranslator->LookupPath(mPath));
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  Path *path = aTranslator->LookupPath(mPath);
+  if (!dt || !path) {
+    return false;
+  }
+  dt->PushClip(path);
+  return true;
 }
 
 void
@@ -719,9 +1079,20 @@ RecordedPushClip::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedPushClip::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mPath.mLongPtr;
+}
+
+bool
 RecordedPushClipRect::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->PushClipRect(mRect);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt) {
+    return false;
+  }
+  dt->PushClipRect(mRect);
+  return true;
 }
 
 void
@@ -744,9 +1115,20 @@ RecordedPushClipRect::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedPushClipRect::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mRect = mRect;
+}
+
+bool
 RecordedPopClip::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->PopClip();
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt) {
+    return false;
+  }
+  dt->PopClip();
+  return true;
 }
 
 void
@@ -766,10 +1148,15 @@ RecordedPopClip::OutputSimpleEventInfo(stringstream &aStringStream) const
   aStringStream << "[" << mDT << "] PopClip";
 }
 
-void
+bool
 RecordedSetTransform::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->SetTransform(mTransform);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt) {
+    return false;
+  }
+  dt->SetTransform(mTransform);
+  return true;
 }
 
 void
@@ -793,11 +1180,21 @@ RecordedSetTransform::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedSetTransform::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mTransform = mTransform;
+}
+
+bool
 RecordedDrawSurface::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->
-    DrawSurface(aTranslator->LookupSourceSurface(mRefSource), mDest, mSource,
-                mDSOptions, mOptions);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  SourceSurface *surface = aTranslator->LookupSourceSurface(mRefSource);
+  if (!dt || !surface) {
+    return false;
+  }
+  dt->DrawSurface(surface, mDest, mSource, mDSOptions, mOptions);
+  return true;
 }
 
 void
@@ -828,11 +1225,23 @@ RecordedDrawSurface::OutputSimpleEventInfo(stringstream &aStringStream) const
 }
 
 void
+RecordedDrawSurface::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mRefSource.mLongPtr;
+  aDescription.mRect = mDest;
+  aDescription.mSourceRect = mSource;
+}
+
+bool
 RecordedDrawSurfaceWithShadow::PlayEvent(Translator *aTranslator) const
 {
-  aTranslator->LookupDrawTarget(mDT)->
-    DrawSurfaceWithShadow(aTranslator->LookupSourceSurface(mRefSource),
-                          mDest, mColor, mOffset, mSigma, mOp);
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  SourceSurface *surface = aTranslator->LookupSourceSurface(mRefSource);
+  if (!dt || !surface) {
+    return false;
+  }
+  dt->DrawSurfaceWithShadow(surface, mDest, mColor, mOffset, mSigma, mOp);
+  return true;
 }
 
 void
@@ -865,6 +1274,16 @@ RecordedDrawSurfaceWithShadow::OutputSimpleEventInfo(stringstream &aStringStream
     mColor.r << ", " << mColor.g << ", " << mColor.b << ", " << mColor.a << ")";
 }
 
+void
+RecordedDrawSurfaceWithShadow::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mRefSource.mLongPtr;
+  aDescription.mPoint1 = mDest;
+  aDescription.mPoint2 = mOffset;
+  aDescription.mColor = mColor;
+  aDescription.mValue = mSigma;
+}
+
 RecordedPathCreation::RecordedPathCreation(PathRecording *aPath)
   : RecordedEvent(PATHCREATION), mRefPtr(aPath), mFillRule(aPath->mFillRule), mPathOps(aPath->mPathOps)
 {
@@ -874,7 +1293,7 @@ RecordedPathCreation::~RecordedPathCreation()
 {
 }
 
-void
+bool
 RecordedPathCreation::PlayEvent(Translator *aTranslator) const
 {
   RefPtr<PathBuilder> builder = 
@@ -903,6 +1322,7 @@ RecordedPathCreation::PlayEvent(Translator *aTranslator) const
 
   RefPtr<Path> path = builder->Finish();
   aTranslator->AddPath(mRefPtr, path);
+  return true;
 }
 
 void
@@ -936,9 +1356,18 @@ RecordedPathCreation::RecordedPathCreation(istream &aStream)
   ReadElement(aStream, size);
   ReadElement(aStream, mFillRule);
 
+  // Every op takes at least the space of its type.
+  if (!CheckElementsAvailable(aStream, size, sizeof(int32_t))) {
+    return;
+  }
+
   for (uint64_t i = 0; i < size; i++) {
     PathOp newPathOp;
     ReadElement(aStream, newPathOp.mType);
+    if (newPathOp.mType < PathOp::OP_MOVETO || newPathOp.mType > PathOp::OP_CLOSE) {
+      RejectEvent(aStream);
+      return;
+    }
     if (sPointCount[newPathOp.mType] >= 1) {
       ReadElement(aStream, newPathOp.mP1);
     }
@@ -959,10 +1388,17 @@ RecordedPathCreation::OutputSimpleEventInfo(stringstream &aStringStream) const
 {
   aStringStream << "[" << mRefPtr << "] Path created (OpCount: " << mPathOps.size() << ")";
 }
+
 void
+RecordedPathCreation::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mCount = uint32_t(mPathOps.size());
+}
+bool
 RecordedPathDestruction::PlayEvent(Translator *aTranslator) const
 {
   aTranslator->RemovePath(mRefPtr);
+  return true;
 }
 
 void
@@ -990,12 +1426,13 @@ RecordedSourceSurfaceCreation::~RecordedSourceSurfaceCreation()
   }
 }
 
-void
+bool
 RecordedSourceSurfaceCreation::PlayEvent(Translator *aTranslator) const
 {
   RefPtr<SourceSurface> src = aTranslator->GetReferenceDrawTarget()->
     CreateSourceSurfaceFromData(mData, mSize, mSize.width * BytesPerPixel(mFormat), mFormat);
   aTranslator->AddSourceSurface(mRefPtr, src);
+  return true;
 }
 
 void
@@ -1005,18 +1442,51 @@ RecordedSourceSurfaceCreation::RecordToStream(ostream &aStream) const
   WriteElement(aStream, mSize);
   WriteElement(aStream, mFormat);
   for (int y = 0; y < mSize.height; y++) {
-    aStream.write((const char*)mData + y * mStride, BytesPerPixel(mFormat) * mSize.width);
+    const uint8_t *row = mData + y * mStride;
+    if (mFormat == FORMAT_R5G6B5) {
+      // Each pixel is a native-endian 16 bit value.
+      for (int x = 0; x < mSize.width; x++) {
+        uint16_t pixel;
+        memcpy(&pixel, row + x * sizeof(uint16_t), sizeof(uint16_t));
+        WriteElement(aStream, pixel);
+      }
+    } else {
+      aStream.write((const char*)row, BytesPerPixel(mFormat) * mSize.width);
+    }
   }
 }
 
 RecordedSourceSurfaceCreation::RecordedSourceSurfaceCreation(istream &aStream)
-  : RecordedEvent(SOURCESURFACECREATION), mDataOwned(true)
+  : RecordedEvent(SOURCESURFACECREATION), mData(NULL), mDataOwned(true)
 {
   ReadElement(aStream, mRefPtr);
   ReadElement(aStream, mSize);
   ReadElement(aStream, mFormat);
-  mData = (uint8_t*)new char[mSize.width * mSize.height * BytesPerPixel(mFormat)];
-  aStream.read((char*)mData, mSize.width * mSize.height * BytesPerPixel(mFormat));
+  if (!IsValidSurface(mSize, mFormat)) {
+    RejectEvent(aStream);
+    return;
+  }
+  uint64_t length = RecordedSurfaceBytes(mSize, mFormat);
+  if (!CheckBytesAvailable(aStream, length)) {
+    return;
+  }
+  if (mFormat == FORMAT_R5G6B5) {
+    vector<uint16_t> pixels;
+    ReadElements(aStream, uint64_t(mSize.width) * mSize.height, pixels);
+    if (!aStream) {
+      return;
+    }
+    mData = (uint8_t*)new char[length];
+    copy(pixels.begin(), pixels.end(), (uint16_t*)mData);
+  } else {
+    vector<uint8_t> data;
+    ReadBytes(aStream, length, data);
+    if (!aStream) {
+      return;
+    }
+    mData = (uint8_t*)new char[length];
+    copy(data.begin(), data.end(), mData);
+  }
 }
 
 void
@@ -1026,9 +1496,17 @@ RecordedSourceSurfaceCreation::OutputSimpleEventInfo(stringstream &aStringStream
 }
 
 void
+RecordedSourceSurfaceCreation::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mSize = mSize;
+  aDescription.mFormat = mFormat;
+}
+
+bool
 RecordedSourceSurfaceDestruction::PlayEvent(Translator *aTranslator) const
 {
   aTranslator->RemoveSourceSurface(mRefPtr);
+  return true;
 }
 
 void
@@ -1056,12 +1534,13 @@ RecordedGradientStopsCreation::~RecordedGradientStopsCreation()
   }
 }
 
-void
+bool
 RecordedGradientStopsCreation::PlayEvent(Translator *aTranslator) const
 {
   RefPtr<GradientStops> src = aTranslator->GetReferenceDrawTarget()->
     CreateGradientStops(mStops, mNumStops, mExtendMode);
   aTranslator->AddGradientStops(mRefPtr, src);
+  return true;
 }
 
 void
@@ -1070,18 +1549,27 @@ RecordedGradientStopsCreation::RecordToStream(ostream &aStream) const
   WriteElement(aStream, mRefPtr);
   WriteElement(aStream, mExtendMode);
   WriteElement(aStream, mNumStops);
-  aStream.write((const char*)mStops, mNumStops * sizeof(GradientStop));
+  for (uint32_t i = 0; i < mNumStops; i++) {
+    WriteElement(aStream, mStops[i]);
+  }
 }
 
 RecordedGradientStopsCreation::RecordedGradientStopsCreation(istream &aStream)
-  : RecordedEvent(GRADIENTSTOPSCREATION), mDataOwned(true)
+  : RecordedEvent(GRADIENTSTOPSCREATION), mStops(NULL), mDataOwned(true)
 {
   ReadElement(aStream, mRefPtr);
   ReadElement(aStream, mExtendMode);
   ReadElement(aStream, mNumStops);
+  if (!CheckElementsAvailable(aStream, mNumStops, 5 * sizeof(Float))) {
+    return;
+  }
+  vector<GradientStop> stops;
+  ReadElements(aStream, mNumStops, stops);
+  if (!aStream) {
+    return;
+  }
   mStops = new GradientStop[mNumStops];
-
-  aStream.read((char*)mStops, mNumStops * sizeof(GradientStop));
+  copy(stops.begin(), stops.end(), mStops);
 }
 
 void
@@ -1091,9 +1579,16 @@ RecordedGradientStopsCreation::OutputSimpleEventInfo(stringstream &aStringStream
 }
 
 void
+RecordedGradientStopsCreation::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mCount = mNumStops;
+}
+
+bool
 RecordedGradientStopsDestruction::PlayEvent(Translator *aTranslator) const
 {
   aTranslator->RemoveGradientStops(mRefPtr);
+  return true;
 }
 
 void
@@ -1114,11 +1609,16 @@ RecordedGradientStopsDestruction::OutputSimpleEventInfo(stringstream &aStringStr
   aStringStream << "[" << mRefPtr << "] GradientStops Destroyed";
 }
 
-void
+bool
 RecordedSnapshot::PlayEvent(Translator *aTranslator) const
 {
-  RefPtr<SourceSurface> src = aTranslator->LookupDrawTarget(mDT)->Snapshot();
+  DrawTarget *dt = aTranslator->LookupDrawTarget(mDT);
+  if (!dt) {
+    return false;
+  }
+  RefPtr<SourceSurface> src = dt->Snapshot();
   aTranslator->AddSourceSurface(mRefPtr, src);
+  return true;
 }
 
 void
@@ -1141,18 +1641,25 @@ RecordedSnapshot::OutputSimpleEventInfo(stringstream &aStringStream) const
   aStringStream << "[" << mRefPtr << "] Snapshot Created (DT: " << mDT << ")";
 }
 
+void
+RecordedSnapshot::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mReference = mDT.mLongPtr;
+}
+
 RecordedScaledFontCreation::~RecordedScaledFontCreation()
 {
   delete [] mData;
 }
 
-void
+bool
 RecordedScaledFontCreation::PlayEvent(Translator *aTranslator) const
 {
   RefPtr<ScaledFont> scaledFont =
     Factory::CreateScaledFontForTrueTypeData(mData, mSize, mIndex, mGlyphSize,
                                              aTranslator->GetDesiredFontType());
   aTranslator->AddScaledFont(mRefPtr, scaledFont);
+  return true;
 }
 
 void
@@ -1171,6 +1678,12 @@ RecordedScaledFontCreation::OutputSimpleEventInfo(stringstream &aStringStream) c
   aStringStream << "[" << mRefPtr << "] ScaledFont Created";
 }
 
+void
+RecordedScaledFontCreation::Describe(RecordedEventDescription &aDescription) const
+{
+  aDescription.mValue = mGlyphSize;
+}
+
 void
 RecordedScaledFontCreation::SetFontData(const uint8_t *aData, uint32_t aSize, uint32_t aIndex, Float aGlyphSize)
 {
@@ -1182,20 +1695,29 @@ RecordedScaledFontCreation::SetFontData(const uint8_t *aData, uint32_t aSize, ui
 }
 
 RecordedScaledFontCreation::RecordedScaledFontCreation(istream &aStream)
-  : RecordedEvent(SCALEDFONTCREATION)
+  : RecordedEvent(SCALEDFONTCREATION), mData(NULL)
 {
   ReadElement(aStream, mRefPtr);
   ReadElement(aStream, mIndex);
   ReadElement(aStream, mGlyphSize);
   ReadElement(aStream, mSize);
+  if (!CheckBytesAvailable(aStream, mSize)) {
+    return;
+  }
+  vector<uint8_t> data;
+  ReadBytes(aStream, mSize, data);
+  if (!aStream) {
+    return;
+  }
   mData = new uint8_t[mSize];
-  aStream.read((char*)mData, mSize);
+  copy(data.begin(), data.end(), mData);
 }
 
-void
+bool
 RecordedScaledFontDestruction::PlayEvent(Translator *aTranslator) const
 {
   aTranslator->RemoveScaledFont(mRefPtr);
+  return true;
 }
 
 void
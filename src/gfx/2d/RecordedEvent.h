@@ -20,11 +20,45 @@ namespace gfx {
 // loss of backwards compatibility. Old streams will not work in a player
 // using a newer major revision. And new streams will not work in a player
 // using an older major revision.
-const uint16_t kMajorRevision = 2;
+const uint16_t kMajorRevision = 3;
 // A change in minor revision means additions of new events. New streams will
 // not play in older players.
 const uint16_t kMinorRevision = 0;
 
+// Written at the start of every stream, before the revision numbers.
+const uint32_t kMagicInt = 0xc001feed;
+
+// Limits on the draw targets and source surfaces a recording may create.
+// Events creating larger ones are rejected as invalid.
+const int32_t kMaxRecordedSurfaceSize = 16384;
+const uint64_t kMaxRecordedSurfaceBytes = 128 * 1024 * 1024;
+// DrawEventPlayer keeps every draw target it creates alive, so it also limits
+// their total size.
+const uint64_t kMaxPlayedDrawTargetBytes = 512 * 1024 * 1024;
+
+// The size of a surface's pixel data, as counted against the limits above.
+// aSize must not be negative.
+uint64_t RecordedSurfaceBytes(const IntSize &aSize, SurfaceFormat aFormat);
+
+// Why reading a recording stopped before its end. Mirrored by AzRecordingError
+// in azure-c.h.
+enum RecordingError
+{
+  RECORDING_ERROR_NONE,
+  // The stream does not start with a recording header.
+  RECORDING_ERROR_BAD_HEADER,
+  // The recording was made with an incompatible major or newer minor revision.
+  RECORDING_ERROR_UNSUPPORTED_VERSION,
+  RECORDING_ERROR_UNKNOWN_EVENT,
+  // The stream ends in the middle of an event.
+  RECORDING_ERROR_TRUNCATED,
+  // An event has an impossible length, size or enum value.
+  RECORDING_ERROR_INVALID_EVENT,
+  // An event refers to an object which was never created or was already
+  // destroyed. Only reported when playing a recording back.
+  RECORDING_ERROR_UNKNOWN_REFERENCE
+};
+
 struct ReferencePtr
 {
   ReferencePtr()
@@ -58,6 +92,19 @@ struct ReferencePtr
   uint64_t mLongPtr;
 };
 
+template<>
+struct ElementStreamFormat<ReferencePtr>
+{
+  static void Write(std::ostream &aStream, const ReferencePtr &aPtr)
+  {
+    WriteElement(aStream, aPtr.mLongPtr);
+  }
+  static void Read(std::istream &aStream, ReferencePtr &aPtr)
+  {
+    ReadElement(aStream, aPtr.mLongPtr);
+  }
+};
+
 // Used by the Azure drawing debugger (player2d)
 inline std::string StringFromPtr(ReferencePtr aPtr)
 {
@@ -120,6 +167,80 @@ struct SurfacePatternStorage
   Matrix mMatrix;
 };
 
+template<>
+struct ElementStreamFormat<ColorPatternStorage>
+{
+  static void Write(std::ostream &aStream, const ColorPatternStorage &aStorage)
+  {
+    WriteElement(aStream, aStorage.mColor);
+  }
+  static void Read(std::istream &aStream, ColorPatternStorage &aStorage)
+  {
+    ReadElement(aStream, aStorage.mColor);
+  }
+};
+
+template<>
+struct ElementStreamFormat<LinearGradientPatternStorage>
+{
+  static void Write(std::ostream &aStream, const LinearGradientPatternStorage &aStorage)
+  {
+    WriteElement(aStream, aStorage.mBegin);
+    WriteElement(aStream, aStorage.mEnd);
+    WriteElement(aStream, aStorage.mStops);
+    WriteElement(aStream, aStorage.mMatrix);
+  }
+  static void Read(std::istream &aStream, LinearGradientPatternStorage &aStorage)
+  {
+    ReadElement(aStream, aStorage.mBegin);
+    ReadElement(aStream, aStorage.mEnd);
+    ReadElement(aStream, aStorage.mStops);
+    ReadElement(aStream, aStorage.mMatrix);
+  }
+};
+
+template<>
+struct ElementStreamFormat<RadialGradientPatternStorage>
+{
+  static void Write(std::ostream &aStream, const RadialGradientPatternStorage &aStorage)
+  {
+    WriteElement(aStream, aStorage.mCenter1);
+    WriteElement(aStream, aStorage.mCenter2);
+    WriteElement(aStream, aStorage.mRadius1);
+    WriteElement(aStream, aStorage.mRadius2);
+    WriteElement(aStream, aStorage.mStops);
+    WriteElement(aStream, aStorage.mMatrix);
+  }
+  static void Read(std::istream &aStream, RadialGradientPatternStorage &aStorage)
+  {
+    ReadElement(aStream, aStorage.mCenter1);
+    ReadElement(aStream, aStorage.mCenter2);
+    ReadElement(aStream, aStorage.mRadius1);
+    ReadElement(aStream, aStorage.mRadius2);
+    ReadElement(aStream, aStorage.mStops);
+    ReadElement(aStream, aStorage.mMatrix);
+  }
+};
+
+template<>
+struct ElementStreamFormat<SurfacePatternStorage>
+{
+  static void Write(std::ostream &aStream, const SurfacePatternStorage &aStorage)
+  {
+    WriteElement(aStream, aStorage.mExtend);
+    WriteElement(aStream, aStorage.mFilter);
+    WriteElement(aStream, aStorage.mSurface);
+    WriteElement(aStream, aStorage.mMatrix);
+  }
+  static void Read(std::istream &aStream, SurfacePatternStorage &aStorage)
+  {
+    ReadElement(aStream, aStorage.mExtend);
+    ReadElement(aStream, aStorage.mFilter);
+    ReadElement(aStream, aStorage.mSurface);
+    ReadElement(aStream, aStorage.mMatrix);
+  }
+};
+
 struct PatternStorage
 {
   PatternType mType;
@@ -132,6 +253,42 @@ struct PatternStorage
   };
 };
 
+// A flat summary of an event's parameters, used to inspect a recording
+// without playing it back. Fields which do not apply to an event are zero,
+// pattern types which do not apply are -1. This is mirrored by
+// AzRecordedEventDescription in azure-c.h.
+struct RecordedEventDescription
+{
+  int32_t mType;
+  // Type of the pattern drawn with, or the source pattern for Mask.
+  int32_t mPatternType;
+  // Type of the mask pattern for Mask.
+  int32_t mMaskPatternType;
+  // SurfaceFormat of created draw targets and source surfaces.
+  int32_t mFormat;
+  // BackendType of created draw targets.
+  int32_t mBackendType;
+  // Number of glyphs, path operations or gradient stops.
+  uint32_t mCount;
+  uint64_t mObject;
+  uint64_t mDestinedDT;
+  // The path, source surface or scaled font used, or the draw target a
+  // snapshot was taken of.
+  uint64_t mReference;
+  Rect mRect;
+  Rect mSourceRect;
+  Point mPoint1;
+  Point mPoint2;
+  Matrix mTransform;
+  // The color of a color pattern or of a shadow.
+  Color mColor;
+  // The color of Mask's mask pattern, if it is a color pattern.
+  Color mMaskColor;
+  IntSize mSize;
+  // The stroke width, shadow sigma or glyph size.
+  Float mValue;
+};
+
 class RecordedEvent {
 public:
   enum EventType {
@@ -163,7 +320,11 @@ public:
     SCALEDFONTDESTRUCTION
   };
 
-  virtual void PlayEvent(Translator *aTranslator) const {}
+  virtual ~RecordedEvent() {}
+
+  // Plays this event through aTranslator. Returns false, without playing
+  // anything, if the event refers to an object the translator doesn't have.
+  virtual bool PlayEvent(Translator *aTranslator) const { return true; }
 
   virtual void RecordToStream(std::ostream &aStream) const {}
 
@@ -181,10 +342,23 @@ public:
 
   virtual ReferencePtr GetDestinedDT() { return nullptr; }
 
+  // Fills in aDescription for this event, see RecordedEventDescription.
+  void GetDescription(RecordedEventDescription &aDescription);
+
   void OutputSimplePatternInfo(const PatternStorage &aStorage, std::stringstream &aOutput) const;
 
   static RecordedEvent *LoadEventFromStream(std::istream &aStream, EventType aType);
 
+  // Reads the header written at the start of every recording. Returns
+  // RECORDING_ERROR_NONE if this player understands the recording.
+  static RecordingError ReadStreamHeader(std::istream &aStream);
+  // Reads the next event in a recording. Returns NULL when there are no more
+  // events, in which case aError is RECORDING_ERROR_NONE, or when the stream
+  // is malformed. Lengths and sizes in the stream are checked, and data is
+  // only allocated as it is read, so this is safe to use on untrusted
+  // recordings, including ones read from streams which can't seek.
+  static RecordedEvent *LoadNextEventFromStream(std::istream &aStream, RecordingError &aError);
+
   EventType GetType() { return (EventType)mType; }
 protected:
   friend class DrawEventRecorderPrivate;
@@ -192,6 +366,10 @@ protected:
   RecordedEvent(int32_t aType) : mType(aType)
   {}
 
+  // Fills in the event specific fields of aDescription.
+  virtual void Describe(RecordedEventDescription &aDescription) const {}
+  void DescribePattern(const PatternStorage &aStorage, int32_t &aPatternType, Color &aColor) const;
+
   int32_t mType;
   std::vector<Float> mDashPatternStorage;
 };
@@ -221,10 +399,11 @@ public:
     : RecordedEvent(DRAWTARGETCREATION), mRefPtr(aRefPtr), mBackendType(aType), mSize(aSize), mFormat(aFormat)
   {}
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "DrawTarget Creation"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -246,7 +425,7 @@ public:
     : RecordedEvent(DRAWTARGETDESTRUCTION), mRefPtr(aRefPtr)
   {}
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
@@ -271,10 +450,11 @@ public:
     StorePattern(mPattern, aPattern);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "FillRect"; }
 private:
@@ -297,10 +477,11 @@ public:
     StorePattern(mPattern, aPattern);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "StrokeRect"; }
 private:
@@ -325,10 +506,11 @@ public:
     StorePattern(mPattern, aPattern);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "StrokeLine"; }
 private:
@@ -351,10 +533,11 @@ public:
     StorePattern(mPattern, aPattern);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "Fill"; }
 private:
@@ -380,10 +563,11 @@ public:
   }
   virtual ~RecordedFillGlyphs();
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "FillGlyphs"; }
 private:
@@ -407,10 +591,11 @@ public:
     StorePattern(mMask, aMask);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "Mask"; }
 private:
@@ -433,10 +618,11 @@ public:
     StorePattern(mPattern, aPattern);
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "Stroke"; }
 private:
@@ -457,10 +643,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "ClearRect"; }
 private:
@@ -480,10 +667,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "CopySurface"; }
 private:
@@ -503,10 +691,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "PushClip"; }
 private:
@@ -524,10 +713,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
 
   virtual std::string GetName() const { return "PushClipRect"; }
 private:
@@ -544,7 +734,7 @@ public:
     : RecordedDrawingEvent(POPCLIP, aDT)
   {}
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
@@ -563,10 +753,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "SetTransform"; }
 private:
@@ -587,10 +778,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "DrawSurface"; }
 private:
@@ -615,10 +807,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "DrawSurfaceWithShadow"; }
 private:
@@ -639,10 +832,11 @@ public:
   RecordedPathCreation(PathRecording *aPath);
   ~RecordedPathCreation();
   
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "Path Creation"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -663,7 +857,7 @@ public:
   {
   }
   
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
@@ -689,10 +883,11 @@ public:
 
   ~RecordedSourceSurfaceCreation();
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "SourceSurface Creation"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -716,7 +911,7 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
@@ -742,10 +937,11 @@ public:
 
   ~RecordedGradientStopsCreation();
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "GradientStops Creation"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -768,7 +964,7 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
@@ -790,10 +986,11 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "Snapshot"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -821,10 +1018,11 @@ public:
 
   ~RecordedScaledFontCreation();
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
+  virtual void Describe(RecordedEventDescription &aDescription) const;
   
   virtual std::string GetName() const { return "ScaledFont Creation"; }
   virtual ReferencePtr GetObject() const { return mRefPtr; }
@@ -850,7 +1048,7 @@ public:
   {
   }
 
-  virtual void PlayEvent(Translator *aTranslator) const;
+  virtual bool PlayEvent(Translator *aTranslator) const;
 
   virtual void RecordToStream(std::ostream &aStream) const;
   virtual void OutputSimpleEventInfo(std::stringstream &aStringStream) const;
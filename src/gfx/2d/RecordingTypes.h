@@ -6,21 +6,109 @@
 #ifndef MOZILLA_GFX_RECORDINGTYPES_H_
 #define MOZILLA_GFX_RECORDINGTYPES_H_
 
+#include "2D.h"
+#include "mozilla/Assertions.h"
+#include "mozilla/Endian.h"
+#include "mozilla/TypeTraits.h"
+#include <istream>
 #include <ostream>
+#include <string.h>
 
 namespace mozilla {
 namespace gfx {
 
+// Recordings are always little-endian, and every structure is written one
+// field at a time, so that a recording made on one platform can be played
+// back on another regardless of byte order, padding or pointer size.
+
+template<size_t Size>
+struct LittleEndianBytes;
+
+template<>
+struct LittleEndianBytes<1>
+{
+  static void Write(char *aDest, const void *aValue) { memcpy(aDest, aValue, 1); }
+  static void Read(const char *aSrc, void *aValue) { memcpy(aValue, aSrc, 1); }
+};
+
+template<>
+struct LittleEndianBytes<2>
+{
+  static void Write(char *aDest, const void *aValue)
+  {
+    uint16_t value;
+    memcpy(&value, aValue, sizeof(value));
+    LittleEndian::writeUint16(aDest, value);
+  }
+  static void Read(const char *aSrc, void *aValue)
+  {
+    uint16_t value = LittleEndian::readUint16(aSrc);
+    memcpy(aValue, &value, sizeof(value));
+  }
+};
+
+template<>
+struct LittleEndianBytes<4>
+{
+  static void Write(char *aDest, const void *aValue)
+  {
+    uint32_t value;
+    memcpy(&value, aValue, sizeof(value));
+    LittleEndian::writeUint32(aDest, value);
+  }
+  static void Read(const char *aSrc, void *aValue)
+  {
+    uint32_t value = LittleEndian::readUint32(aSrc);
+    memcpy(aValue, &value, sizeof(value));
+  }
+};
+
+template<>
+struct LittleEndianBytes<8>
+{
+  static void Write(char *aDest, const void *aValue)
+  {
+    uint64_t value;
+    memcpy(&value, aValue, sizeof(value));
+    LittleEndian::writeUint64(aDest, value);
+  }
+  static void Read(const char *aSrc, void *aValue)
+  {
+    uint64_t value = LittleEndian::readUint64(aSrc);
+    memcpy(aValue, &value, sizeof(value));
+  }
+};
+
+// Handles integers, floats and enums. Enums are stored as 32 bit integers
+// since their size is up to the compiler. Structures need a specialization
+// which writes their fields.
 template<class T>
 struct ElementStreamFormat
 {
+  typedef typename Conditional<IsIntegral<T>::value ||
+                               IsSame<T, float>::value ||
+                               IsSame<T, double>::value,
+                               T, int32_t>::Type StoredType;
+
   static void Write(std::ostream &aStream, const T &aElement)
   {
-    aStream.write(reinterpret_cast<const char*>(&aElement), sizeof(T));
+    MOZ_STATIC_ASSERT((IsConvertible<T, int64_t>::value),
+                      "structures need their own ElementStreamFormat");
+    StoredType value = StoredType(aElement);
+    char buffer[sizeof(StoredType)];
+    LittleEndianBytes<sizeof(StoredType)>::Write(buffer, &value);
+    aStream.write(buffer, sizeof(buffer));
   }
   static void Read(std::istream &aStream, T &aElement)
   {
-    aStream.read(reinterpret_cast<char *>(&aElement), sizeof(T));
+    MOZ_STATIC_ASSERT((IsConvertible<T, int64_t>::value),
+                      "structures need their own ElementStreamFormat");
+    // Left zeroed if the stream ends early, callers check the stream state.
+    char buffer[sizeof(StoredType)] = { 0 };
+    aStream.read(buffer, sizeof(buffer));
+    StoredType value;
+    LittleEndianBytes<sizeof(StoredType)>::Read(buffer, &value);
+    aElement = T(value);
   }
 };
 
@@ -35,6 +123,206 @@ void ReadElement(std::istream &aStream, T &aElement)
   ElementStreamFormat<T>::Read(aStream, aElement);
 }
 
+template<>
+struct ElementStreamFormat<IntSize>
+{
+  static void Write(std::ostream &aStream, const IntSize &aSize)
+  {
+    WriteElement(aStream, aSize.width);
+    WriteElement(aStream, aSize.height);
+  }
+  static void Read(std::istream &aStream, IntSize &aSize)
+  {
+    ReadElement(aStream, aSize.width);
+    ReadElement(aStream, aSize.height);
+  }
+};
+
+template<>
+struct ElementStreamFormat<Point>
+{
+  static void Write(std::ostream &aStream, const Point &aPoint)
+  {
+    WriteElement(aStream, aPoint.x);
+    WriteElement(aStream, aPoint.y);
+  }
+  static void Read(std::istream &aStream, Point &aPoint)
+  {
+    ReadElement(aStream, aPoint.x);
+    ReadElement(aStream, aPoint.y);
+  }
+};
+
+template<>
+struct ElementStreamFormat<IntPoint>
+{
+  static void Write(std::ostream &aStream, const IntPoint &aPoint)
+  {
+    WriteElement(aStream, aPoint.x);
+    WriteElement(aStream, aPoint.y);
+  }
+  static void Read(std::istream &aStream, IntPoint &aPoint)
+  {
+    ReadElement(aStream, aPoint.x);
+    ReadElement(aStream, aPoint.y);
+  }
+};
+
+template<>
+struct ElementStreamFormat<Rect>
+{
+  static void Write(std::ostream &aStream, const Rect &aRect)
+  {
+    WriteElement(aStream, aRect.x);
+    WriteElement(aStream, aRect.y);
+    WriteElement(aStream, aRect.width);
+    WriteElement(aStream, aRect.height);
+  }
+  static void Read(std::istream &aStream, Rect &aRect)
+  {
+    ReadElement(aStream, aRect.x);
+    ReadElement(aStream, aRect.y);
+    ReadElement(aStream, aRect.width);
+    ReadElement(aStream, aRect.height);
+  }
+};
+
+template<>
+struct ElementStreamFormat<IntRect>
+{
+  static void Write(std::ostream &aStream, const IntRect &aRect)
+  {
+    WriteElement(aStream, aRect.x);
+    WriteElement(aStream, aRect.y);
+    WriteElement(aStream, aRect.width);
+    WriteElement(aStream, aRect.height);
+  }
+  static void Read(std::istream &aStream, IntRect &aRect)
+  {
+    ReadElement(aStream, aRect.x);
+    ReadElement(aStream, aRect.y);
+    ReadElement(aStream, aRect.width);
+    ReadElement(aStream, aRect.height);
+  }
+};
+
+template<>
+struct ElementStreamFormat<Matrix>
+{
+  static void Write(std::ostream &aStream, const Matrix &aMatrix)
+  {
+    WriteElement(aStream, aMatrix._11);
+    WriteElement(aStream, aMatrix._12);
+    WriteElement(aStream, aMatrix._21);
+    WriteElement(aStream, aMatrix._22);
+    WriteElement(aStream, aMatrix._31);
+    WriteElement(aStream, aMatrix._32);
+  }
+  static void Read(std::istream &aStream, Matrix &aMatrix)
+  {
+    ReadElement(aStream, aMatrix._11);
+    ReadElement(aStream, aMatrix._12);
+    ReadElement(aStream, aMatrix._21);
+    ReadElement(aStream, aMatrix._22);
+    ReadElement(aStream, aMatrix._31);
+    ReadElement(aStream, aMatrix._32);
+  }
+};
+
+template<>
+struct ElementStreamFormat<Color>
+{
+  static void Write(std::ostream &aStream, const Color &aColor)
+  {
+    WriteElement(aStream, aColor.r);
+    WriteElement(aStream, aColor.g);
+    WriteElement(aStream, aColor.b);
+    WriteElement(aStream, aColor.a);
+  }
+  static void Read(std::istream &aStream, Color &aColor)
+  {
+    ReadElement(aStream, aColor.r);
+    ReadElement(aStream, aColor.g);
+    ReadElement(aStream, aColor.b);
+    ReadElement(aStream, aColor.a);
+  }
+};
+
+template<>
+struct ElementStreamFormat<Glyph>
+{
+  static void Write(std::ostream &aStream, const Glyph &aGlyph)
+  {
+    WriteElement(aStream, aGlyph.mIndex);
+    WriteElement(aStream, aGlyph.mPosition);
+  }
+  static void Read(std::istream &aStream, Glyph &aGlyph)
+  {
+    ReadElement(aStream, aGlyph.mIndex);
+    ReadElement(aStream, aGlyph.mPosition);
+  }
+};
+
+template<>
+struct ElementStreamFormat<GradientStop>
+{
+  static void Write(std::ostream &aStream, const GradientStop &aStop)
+  {
+    WriteElement(aStream, aStop.offset);
+    WriteElement(aStream, aStop.color);
+  }
+  static void Read(std::istream &aStream, GradientStop &aStop)
+  {
+    ReadElement(aStream, aStop.offset);
+    ReadElement(aStream, aStop.color);
+  }
+};
+
+// The bitfields can't be passed by reference, so go through locals.
+template<>
+struct ElementStreamFormat<DrawOptions>
+{
+  static void Write(std::ostream &aStream, const DrawOptions &aOptions)
+  {
+    WriteElement(aStream, aOptions.mAlpha);
+    WriteElement(aStream, CompositionOp(aOptions.mCompositionOp));
+    WriteElement(aStream, AntialiasMode(aOptions.mAntialiasMode));
+    WriteElement(aStream, Snapping(aOptions.mSnapping));
+  }
+  static void Read(std::istream &aStream, DrawOptions &aOptions)
+  {
+    CompositionOp compositionOp;
+    AntialiasMode antialiasMode;
+    Snapping snapping;
+    ReadElement(aStream, aOptions.mAlpha);
+    ReadElement(aStream, compositionOp);
+    ReadElement(aStream, antialiasMode);
+    ReadElement(aStream, snapping);
+    aOptions.mCompositionOp = compositionOp;
+    aOptions.mAntialiasMode = antialiasMode;
+    aOptions.mSnapping = snapping;
+  }
+};
+
+template<>
+struct ElementStreamFormat<DrawSurfaceOptions>
+{
+  static void Write(std::ostream &aStream, const DrawSurfaceOptions &aOptions)
+  {
+    WriteElement(aStream, Filter(aOptions.mFilter));
+    WriteElement(aStream, SamplingBounds(aOptions.mSamplingBounds));
+  }
+  static void Read(std::istream &aStream, DrawSurfaceOptions &aOptions)
+  {
+    Filter filter;
+    SamplingBounds samplingBounds;
+    ReadElement(aStream, filter);
+    ReadElement(aStream, samplingBounds);
+    aOptions.mFilter = filter;
+    aOptions.mSamplingBounds = samplingBounds;
+  }
+};
+
 }
 }
 
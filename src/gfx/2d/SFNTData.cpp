@@ -0,0 +1,167 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "SFNTData.h"
+
+#include "mozilla/Endian.h"
+
+#include <algorithm>
+#include <utility>
+
+namespace mozilla {
+namespace gfx {
+
+// 'ttcf'
+static const uint32_t kTTCTag = 0x74746366;
+// 'CFF '
+static const uint32_t kCFFTag = 0x43464620;
+// 'OTTO'
+static const uint32_t kCFFVersion = 0x4f54544f;
+static const uint32_t kTrueTypeVersion = 0x00010000;
+
+static const uint32_t kTTCHeaderLength = 12;
+static const uint32_t kOffsetTableLength = 12;
+static const uint32_t kTableRecordLength = 16;
+
+bool
+CopySFNTFace(const uint8_t *aData, uint32_t aDataLength,
+             uint32_t aFaceIndex, std::vector<uint8_t> &aFace)
+{
+  if (aDataLength < 4) {
+    return false;
+  }
+
+  if (BigEndian::readUint32(aData) != kTTCTag) {
+    if (aFaceIndex != 0) {
+      return false;
+    }
+    aFace.assign(aData, aData + aDataLength);
+    return true;
+  }
+
+  if (aDataLength < kTTCHeaderLength) {
+    return false;
+  }
+  uint32_t numFonts = BigEndian::readUint32(aData + 8);
+  if (aFaceIndex >= numFonts ||
+      kTTCHeaderLength + (uint64_t(aFaceIndex) + 1) * 4 > aDataLength) {
+    return false;
+  }
+
+  uint32_t offset = BigEndian::readUint32(aData + kTTCHeaderLength + aFaceIndex * 4);
+  if (uint64_t(offset) + kOffsetTableLength > aDataLength) {
+    return false;
+  }
+  const uint8_t *offsetTable = aData + offset;
+  uint16_t numTables = BigEndian::readUint16(offsetTable + 4);
+  uint32_t headerLength = kOffsetTableLength + numTables * kTableRecordLength;
+  if (uint64_t(offset) + headerLength > aDataLength) {
+    return false;
+  }
+
+  // Every table must lie within the data, and no two tables of the face may
+  // overlap. Otherwise a small collection could point thousands of table
+  // records at one large table and have it copied for each of them. This way
+  // the face is no larger than its header, aDataLength and the padding.
+  std::vector<std::pair<uint32_t, uint32_t> > tables;
+  for (uint16_t i = 0; i < numTables; i++) {
+    const uint8_t *record = offsetTable + kOffsetTableLength + i * kTableRecordLength;
+    uint32_t tableOffset = BigEndian::readUint32(record + 8);
+    uint32_t tableLength = BigEndian::readUint32(record + 12);
+    if (uint64_t(tableOffset) + tableLength > aDataLength) {
+      return false;
+    }
+    tables.push_back(std::make_pair(tableOffset, tableLength));
+  }
+  std::sort(tables.begin(), tables.end());
+  for (size_t i = 1; i < tables.size(); i++) {
+    if (uint64_t(tables[i - 1].first) + tables[i - 1].second > tables[i].first) {
+      return false;
+    }
+  }
+  uint64_t faceLength = uint64_t(headerLength) + aDataLength + 3 * uint64_t(numTables);
+  if (faceLength > UINT32_MAX) {
+    return false;
+  }
+
+  // The table records keep their order; only their offsets change, since the
+  // tables are laid out one after another behind the header.
+  aFace.assign(offsetTable, offsetTable + headerLength);
+  for (uint16_t i = 0; i < numTables; i++) {
+    uint32_t record = kOffsetTableLength + i * kTableRecordLength;
+    uint32_t tableOffset = BigEndian::readUint32(&aFace[record + 8]);
+    uint32_t tableLength = BigEndian::readUint32(&aFace[record + 12]);
+
+    BigEndian::writeUint32(&aFace[record + 8], uint32_t(aFace.size()));
+    aFace.insert(aFace.end(), aData + tableOffset, aData + tableOffset + tableLength);
+    // Tables start on four byte boundaries.
+    aFace.resize((aFace.size() + 3) & ~size_t(3), 0);
+  }
+  return true;
+}
+
+static bool
+TableTagLessThan(const SFNTTable &aA, const SFNTTable &aB)
+{
+  return aA.mTag < aB.mTag;
+}
+
+static uint32_t
+TableChecksum(const uint8_t *aData, uint32_t aLength)
+{
+  uint32_t sum = 0;
+  uint32_t i = 0;
+  for (; i + 4 <= aLength; i += 4) {
+    sum += BigEndian::readUint32(aData + i);
+  }
+  // The last word is padded with zeroes.
+  uint8_t last[4] = { 0, 0, 0, 0 };
+  for (uint32_t j = 0; i + j < aLength; j++) {
+    last[j] = aData[i + j];
+  }
+  return sum + BigEndian::readUint32(last);
+}
+
+void
+BuildSFNT(const std::vector<SFNTTable> &aTables, std::vector<uint8_t> &aFont)
+{
+  std::vector<SFNTTable> tables(aTables);
+  std::sort(tables.begin(), tables.end(), TableTagLessThan);
+
+  uint32_t version = kTrueTypeVersion;
+  uint16_t numTables = uint16_t(tables.size());
+  uint16_t entrySelector = 0;
+  while ((2u << entrySelector) <= numTables) {
+    entrySelector++;
+  }
+  uint16_t searchRange = uint16_t((1u << entrySelector) * kTableRecordLength);
+  uint16_t rangeShift = uint16_t(numTables * kTableRecordLength - searchRange);
+  for (uint16_t i = 0; i < numTables; i++) {
+    if (tables[i].mTag == kCFFTag) {
+      version = kCFFVersion;
+    }
+  }
+
+  aFont.assign(kOffsetTableLength + numTables * kTableRecordLength, 0);
+  BigEndian::writeUint32(&aFont[0], version);
+  BigEndian::writeUint16(&aFont[4], numTables);
+  BigEndian::writeUint16(&aFont[6], searchRange);
+  BigEndian::writeUint16(&aFont[8], entrySelector);
+  BigEndian::writeUint16(&aFont[10], rangeShift);
+
+  for (uint16_t i = 0; i < numTables; i++) {
+    const SFNTTable &table = tables[i];
+    uint32_t record = kOffsetTableLength + i * kTableRecordLength;
+    BigEndian::writeUint32(&aFont[record], table.mTag);
+    BigEndian::writeUint32(&aFont[record + 4], TableChecksum(table.mData, table.mLength));
+    BigEndian::writeUint32(&aFont[record + 8], uint32_t(aFont.size()));
+    BigEndian::writeUint32(&aFont[record + 12], table.mLength);
+    aFont.insert(aFont.end(), table.mData, table.mData + table.mLength);
+    aFont.resize((aFont.size() + 3) & ~size_t(3), 0);
+  }
+}
+
+}
+}
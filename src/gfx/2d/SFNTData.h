@@ -0,0 +1,40 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#ifndef MOZILLA_GFX_SFNTDATA_H_
+#define MOZILLA_GFX_SFNTDATA_H_
+
+#include "Types.h"
+
+#include <vector>
+
+namespace mozilla {
+namespace gfx {
+
+/* Copies face aFaceIndex out of the TrueType or OpenType data in aData into
+ * aFace as a standalone font file. If aData is a TrueType collection the face's
+ * tables are gathered up behind its own header; otherwise the data is copied
+ * as it is, and only face 0 exists. Returns false if there is no such face or
+ * the data is malformed.
+ */
+bool CopySFNTFace(const uint8_t *aData, uint32_t aDataLength,
+                  uint32_t aFaceIndex, std::vector<uint8_t> &aFace);
+
+struct SFNTTable
+{
+  uint32_t mTag;
+  const uint8_t *mData;
+  uint32_t mLength;
+};
+
+/* Lays aTables out as a font file in aFont, behind a header that lists them
+ * in tag order. The tables' contents are copied as they are.
+ */
+void BuildSFNT(const std::vector<SFNTTable> &aTables, std::vector<uint8_t> &aFont);
+
+}
+}
+
+#endif /* MOZILLA_GFX_SFNTDATA_H_ */
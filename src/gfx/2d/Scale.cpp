@@ -5,46 +5,73 @@
 #include "Scale.h"
 
 #ifdef USE_SKIA
-#include "HelpersSkia.h"
 #include "SkBitmap.h"
 #include "image_operations.h"
 #endif
 
+#include <string.h>
+
 namespace mozilla {
 namespace gfx {
 
+#ifdef USE_SKIA
+static skia::ImageOperations::ResizeMethod
+ToSkiaResizeMethod(ResizeMethod aMethod)
+{
+  switch (aMethod) {
+  case RESIZE_GOOD:
+    return skia::ImageOperations::RESIZE_GOOD;
+  case RESIZE_BETTER:
+    return skia::ImageOperations::RESIZE_BETTER;
+  case RESIZE_BOX:
+    return skia::ImageOperations::RESIZE_BOX;
+  case RESIZE_HAMMING1:
+    return skia::ImageOperations::RESIZE_HAMMING1;
+  case RESIZE_LANCZOS2:
+    return skia::ImageOperations::RESIZE_LANCZOS2;
+  case RESIZE_LANCZOS3:
+    return skia::ImageOperations::RESIZE_LANCZOS3;
+  default:
+    return skia::ImageOperations::RESIZE_BEST;
+  }
+}
+#endif
+
 bool Scale(uint8_t* srcData, int32_t srcWidth, int32_t srcHeight, int32_t srcStride,
            uint8_t* dstData, int32_t dstWidth, int32_t dstHeight, int32_t dstStride,
-           SurfaceFormat format)
+           SurfaceFormat format, ResizeMethod method)
 {
 #ifdef USE_SKIA
-  bool opaque;
-  if (format == FORMAT_B8G8R8A8) {
-    opaque = false;
-  } else {
-    opaque = true;
+  // The rescaler reads and writes 32 bpp only, and the destination is in the
+  // same format as the source.
+  if (format != FORMAT_B8G8R8A8 && format != FORMAT_B8G8R8X8) {
+    return false;
   }
 
-  SkBitmap::Config config = GfxFormatToSkiaConfig(format);
-
   SkBitmap imgSrc;
-  imgSrc.setConfig(config, srcWidth, srcHeight, srcStride);
+  imgSrc.setConfig(SkBitmap::kARGB_8888_Config, srcWidth, srcHeight, srcStride);
   imgSrc.setPixels(srcData);
-  imgSrc.setIsOpaque(opaque);
-
-  // Rescaler is compatible with 32 bpp only. Convert to RGB32 if needed.
-  if (config != SkBitmap::kARGB_8888_Config) {
-    imgSrc.copyTo(&imgSrc, SkBitmap::kARGB_8888_Config);
-  }
+  imgSrc.setIsOpaque(format == FORMAT_B8G8R8X8);
 
-  // This returns an SkBitmap backed by dstData; since it also wrote to dstData,
-  // we don't need to look at that SkBitmap.
+  // The rescaler writes rows with no padding, so it can only write straight
+  // into dstData if that has the same stride.
+  bool packed = dstStride == dstWidth * 4;
   SkBitmap result = skia::ImageOperations::Resize(imgSrc,
-                                                  skia::ImageOperations::RESIZE_BEST,
+                                                  ToSkiaResizeMethod(method),
                                                   dstWidth, dstHeight,
-                                                  dstData);
+                                                  packed ? dstData : nullptr);
+  if (result.isNull()) {
+    return false;
+  }
 
-  return !result.isNull();
+  if (!packed) {
+    SkAutoLockPixels locker(result);
+    const uint8_t *resultData = static_cast<const uint8_t*>(result.getPixels());
+    for (int32_t y = 0; y < dstHeight; y++) {
+      memcpy(dstData + y * dstStride, resultData + y * result.rowBytes(), dstWidth * 4);
+    }
+  }
+  return true;
 #else
   return false;
 #endif
@@ -10,11 +10,25 @@
 namespace mozilla {
 namespace gfx {
 
+/* The resampling filters Scale can use. The quality tiers leave the choice of
+ * filter to Scale; the others name one. */
+enum ResizeMethod
+{
+  RESIZE_GOOD,
+  RESIZE_BETTER,
+  RESIZE_BEST,
+  RESIZE_BOX,
+  RESIZE_HAMMING1,
+  RESIZE_LANCZOS2,
+  RESIZE_LANCZOS3
+};
+
 /**
  * Scale an image using a high-quality filter.
  *
- * Synchronously scales an image and writes the output to the destination in
- * 32-bit format. The destination must be pre-allocated by the caller.
+ * Synchronously scales an image with the given filter and writes the output to
+ * the destination, in the same format. The destination must be pre-allocated by
+ * the caller. Only the 32-bit formats, B8G8R8A8 and B8G8R8X8, are supported.
  *
  * Returns true if scaling was successful, and false otherwise. Currently, this
  * function is implemented using Skia. If Skia is not enabled when building,
@@ -28,7 +42,7 @@ namespace gfx {
  */
 GFX2D_API bool Scale(uint8_t* srcData, int32_t srcWidth, int32_t srcHeight, int32_t srcStride,
                      uint8_t* dstData, int32_t dstWidth, int32_t dstHeight, int32_t dstStride,
-                     SurfaceFormat format);
+                     SurfaceFormat format, ResizeMethod method = RESIZE_BEST);
 
 }
 }
@@ -44,29 +44,118 @@ ScaledFontBase::ScaledFontBase(Float aSize)
 #endif
 }
 
+#ifdef USE_SKIA
+void
+ScaledFontBase::SetupSkiaPaint(SkPaint &aPaint)
+{
+  aPaint.setTypeface(GetSkTypeface());
+  aPaint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
+  aPaint.setTextSize(SkFloatToScalar(mSize));
+}
+
+void
+ScaledFontBase::GetFontMetrics(Float &aAscent, Float &aDescent, Float &aLineGap)
+{
+  SkPaint paint;
+  SetupSkiaPaint(paint);
+
+  // Skia measures upwards distances as negative.
+  SkPaint::FontMetrics metrics;
+  paint.getFontMetrics(&metrics);
+  aAscent = -SkScalarToFloat(metrics.fAscent);
+  aDescent = SkScalarToFloat(metrics.fDescent);
+  aLineGap = SkScalarToFloat(metrics.fLeading);
+}
+
+Float
+ScaledFontBase::GetGlyphAdvance(uint32_t aGlyph)
+{
+  // Skia glyph ids are 16 bits; don't let larger ones wrap onto other glyphs.
+  if (aGlyph > 0xffff) {
+    return 0;
+  }
+
+  SkPaint paint;
+  SetupSkiaPaint(paint);
+
+  uint16_t glyph = aGlyph;
+  return SkScalarToFloat(paint.measureText(&glyph, sizeof(glyph)));
+}
+
+Rect
+ScaledFontBase::GetGlyphBounds(uint32_t aGlyph)
+{
+  if (aGlyph > 0xffff) {
+    return Rect();
+  }
+
+  SkPaint paint;
+  SetupSkiaPaint(paint);
+
+  uint16_t glyph = aGlyph;
+  SkRect bounds;
+  paint.measureText(&glyph, sizeof(glyph), &bounds);
+  return Rect(SkScalarToFloat(bounds.fLeft), SkScalarToFloat(bounds.fTop),
+              SkScalarToFloat(bounds.width()), SkScalarToFloat(bounds.height()));
+}
+
+void
+ScaledFontBase::GetGlyphIndices(const uint32_t *aCodepoints, uint32_t aCount, uint32_t *aGlyphs)
+{
+  if (!aCount) {
+    return;
+  }
+
+  SkPaint paint;
+  SetupSkiaPaint(paint);
+  paint.setTextEncoding(SkPaint::kUTF32_TextEncoding);
+
+  std::vector<uint16_t> glyphs(aCount);
+  paint.textToGlyphs(aCodepoints, aCount * sizeof(uint32_t), &glyphs.front());
+  for (uint32_t i = 0; i < aCount; i++) {
+    aGlyphs[i] = glyphs[i];
+  }
+}
+
+SkPath
+ScaledFontBase::GetSkiaPathForGlyphs(const GlyphBuffer &aBuffer)
+{
+  SkPath path;
+  if (!aBuffer.mNumGlyphs) {
+    return path;
+  }
+
+  SkPaint paint;
+  SetupSkiaPaint(paint);
+
+  std::vector<uint16_t> indices;
+  std::vector<SkPoint> offsets;
+  indices.resize(aBuffer.mNumGlyphs);
+  offsets.resize(aBuffer.mNumGlyphs);
+
+  for (unsigned int i = 0; i < aBuffer.mNumGlyphs; i++) {
+    indices[i] = aBuffer.mGlyphs[i].mIndex;
+    offsets[i].fX = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.x);
+    offsets[i].fY = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.y);
+  }
+
+  paint.getPosTextPath(&indices.front(), aBuffer.mNumGlyphs*2, &offsets.front(), &path);
+  return path;
+}
+
+static Point
+SkPointToPoint(const SkPoint &aPoint)
+{
+  return Point(SkScalarToFloat(aPoint.fX), SkScalarToFloat(aPoint.fY));
+}
+#endif
+
 TemporaryRef<Path>
 ScaledFontBase::GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *aTarget)
 {
 #ifdef USE_SKIA
   if (aTarget->GetType() == BACKEND_SKIA) {
-    SkPaint paint;
-    paint.setTypeface(GetSkTypeface());
-    paint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
-    paint.setTextSize(SkFloatToScalar(mSize));
-
-    std::vector<uint16_t> indices;
-    std::vector<SkPoint> offsets;
-    indices.resize(aBuffer.mNumGlyphs);
-    offsets.resize(aBuffer.mNumGlyphs);
-
-    for (unsigned int i = 0; i < aBuffer.mNumGlyphs; i++) {
-      indices[i] = aBuffer.mGlyphs[i].mIndex;
-      offsets[i].fX = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.x);
-      offsets[i].fY = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.y);
-    }
-
-    SkPath path;
-    paint.getPosTextPath(&indices.front(), aBuffer.mNumGlyphs*2, &offsets.front(), &path);
+    SkPath path = GetSkiaPathForGlyphs(aBuffer);
     return new PathSkia(path, FILL_WINDING);
   }
 #endif
@@ -101,9 +190,49 @@ ScaledFontBase::GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *a
 void
 ScaledFontBase::CopyGlyphsToBuilder(const GlyphBuffer &aBuffer, PathBuilder *aBuilder)
 {
-  // XXX - implement me
+#ifdef USE_SKIA
+  if (GetSkTypeface()) {
+    SkPath path = GetSkiaPathForGlyphs(aBuffer);
+    SkPath::Iter iter(path, false);
+    SkPoint points[4];
+    SkPath::Verb verb;
+    while ((verb = iter.next(points)) != SkPath::kDone_Verb) {
+      switch (verb) {
+      case SkPath::kMove_Verb:
+        aBuilder->MoveTo(SkPointToPoint(points[0]));
+        break;
+      case SkPath::kLine_Verb:
+        aBuilder->LineTo(SkPointToPoint(points[1]));
+        break;
+      case SkPath::kQuad_Verb:
+        aBuilder->QuadraticBezierTo(SkPointToPoint(points[1]), SkPointToPoint(points[2]));
+        break;
+      case SkPath::kCubic_Verb:
+        aBuilder->BezierTo(SkPointToPoint(points[1]), SkPointToPoint(points[2]),
+                           SkPointToPoint(points[3]));
+        break;
+      case SkPath::kClose_Verb:
+        aBuilder->Close();
+        break;
+      default:
+        break;
+      }
+    }
+    return;
+  }
+#endif
+  // XXX - implement me for cairo
   MOZ_ASSERT(false);
-  return;
+}
+
+bool
+ScaledFontBase::CanCopyGlyphsToBuilder()
+{
+#ifdef USE_SKIA
+  return !!GetSkTypeface();
+#else
+  return false;
+#endif
 }
 
 #ifdef USE_CAIRO
@@ -9,6 +9,8 @@
 #include "2D.h"
 
 #ifdef USE_SKIA
+#include "SkPaint.h"
+#include "SkPath.h"
 #include "SkTypeface.h"
 #endif
 #ifdef USE_CAIRO
@@ -30,10 +32,29 @@ public:
 
   virtual void CopyGlyphsToBuilder(const GlyphBuffer &aBuffer, PathBuilder *aBuilder);
 
+  /* Whether CopyGlyphsToBuilder can get outlines from this font. Here that
+   * needs a Skia typeface; fonts with another way of getting outlines
+   * override this along with CopyGlyphsToBuilder. */
+  virtual bool CanCopyGlyphsToBuilder();
+
   float GetSize() { return mSize; }
 
 #ifdef USE_SKIA
   virtual SkTypeface* GetSkTypeface() { return mTypeface; }
+
+  /* Font-wide metrics at this font's size. The ascent is the distance from
+   * the baseline to the top of the tallest glyphs and the descent the distance
+   * down to the bottom of the lowest, both positive. */
+  void GetFontMetrics(Float &aAscent, Float &aDescent, Float &aLineGap);
+  /* Glyph ids above 0xffff, which no TrueType or OpenType font can have, get
+   * a zero advance and empty bounds. */
+  Float GetGlyphAdvance(uint32_t aGlyph);
+  /* The bounds of the glyph's ink relative to its origin, in the same space
+   * as glyph positions, so that y grows downwards. */
+  Rect GetGlyphBounds(uint32_t aGlyph);
+  /* Maps each of the aCount code points in aCodepoints to a glyph through the
+   * font's character map, writing glyph 0 for characters it lacks. */
+  void GetGlyphIndices(const uint32_t *aCodepoints, uint32_t aCount, uint32_t *aGlyphs);
 #endif
 
   // Not true, but required to instantiate a ScaledFontBase.
@@ -47,6 +68,9 @@ public:
 protected:
   friend class DrawTargetSkia;
 #ifdef USE_SKIA
+  void SetupSkiaPaint(SkPaint &aPaint);
+  SkPath GetSkiaPathForGlyphs(const GlyphBuffer &aBuffer);
+
   SkTypeface* mTypeface;
 #endif
 #ifdef USE_CAIRO
@@ -55,6 +79,27 @@ protected:
   Float mSize;
 };
 
+#ifdef USE_SKIA
+class GlyphRenderingOptionsSkia : public GlyphRenderingOptions
+{
+public:
+  GlyphRenderingOptionsSkia(FontHinting aHinting, bool aSubpixelPositioning)
+    : mHinting(aHinting)
+    , mSubpixelPositioning(aSubpixelPositioning)
+  {
+  }
+
+  virtual FontType GetType() const { return FONT_SKIA; }
+
+  FontHinting GetHinting() const { return mHinting; }
+  bool GetSubpixelPositioning() const { return mSubpixelPositioning; }
+
+private:
+  FontHinting mHinting;
+  bool mSubpixelPositioning;
+};
+#endif
+
 }
 }
 
@@ -5,13 +5,16 @@
 
 #include "ScaledFontFreetype.h"
 #include "Logging.h"
+#include "SFNTData.h"
 
 #ifdef USE_SKIA
 #include "SkTypeface.h"
+#include "SkData.h"
 #include "SkStream.h"
 #endif
 
 #include <string>
+#include <vector>
 
 using namespace std;
 
@@ -40,12 +43,14 @@ fontStyleToSkia(FontStyle aStyle)
 
 ScaledFontFreetype::ScaledFontFreetype(FontOptions* aFont, Float aSize)
   : ScaledFontBase(aSize)
+#ifdef USE_SKIA
+  , mFontData(nullptr)
+#endif
 {
 #ifdef USE_SKIA
   if (aFont->mData)
   {
-    SkStream *stream = new SkMemoryStream(aFont->mData, aFont->mDataSize, true);
-    mTypeface = SkTypeface::CreateFromStream(stream);
+    InitFromData(aFont->mData, aFont->mDataSize, aFont->mFaceIndex);
   }
   else
   {
@@ -54,5 +59,57 @@ ScaledFontFreetype::ScaledFontFreetype(FontOptions* aFont, Float aSize)
 #endif
 }
 
+ScaledFontFreetype::ScaledFontFreetype(uint8_t *aData, uint32_t aSize,
+                                       uint32_t aFaceIndex, Float aGlyphSize)
+  : ScaledFontBase(aGlyphSize)
+#ifdef USE_SKIA
+  , mFontData(nullptr)
+#endif
+{
+#ifdef USE_SKIA
+  InitFromData(aData, aSize, aFaceIndex);
+#endif
+}
+
+ScaledFontFreetype::~ScaledFontFreetype()
+{
+#ifdef USE_SKIA
+  SkSafeUnref(mFontData);
+#endif
+}
+
+#ifdef USE_SKIA
+void
+ScaledFontFreetype::InitFromData(const uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex)
+{
+  // Skia only loads the first face of a stream, so faces in a collection are
+  // copied out into a font file of their own first. The copy is shared with
+  // Skia's stream, so the caller's data needn't outlive the typeface.
+  vector<uint8_t> face;
+  if (!CopySFNTFace(aData, aSize, aFaceIndex, face)) {
+    gfxWarning() << "Unable to find face " << aFaceIndex << " in truetype data";
+    return;
+  }
+
+  mFontData = SkData::NewWithCopy(&face.front(), face.size());
+  SkStream *stream = new SkMemoryStream(mFontData);
+  mTypeface = SkTypeface::CreateFromStream(stream);
+  stream->unref();
+}
+
+bool
+ScaledFontFreetype::GetFontFileData(FontFileDataOutput aDataCallback, void *aBaton)
+{
+  // Fonts looked up by name have no data of their own.
+  if (!mFontData || !mTypeface) {
+    return false;
+  }
+
+  aDataCallback(static_cast<const uint8_t*>(mFontData->data()), uint32_t(mFontData->size()),
+                0, mSize, aBaton);
+  return true;
+}
+#endif
+
 }
 }
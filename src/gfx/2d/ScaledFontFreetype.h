@@ -8,6 +8,10 @@
 
 #include "ScaledFontBase.h"
 
+#ifdef USE_SKIA
+class SkData;
+#endif
+
 namespace mozilla {
 namespace gfx {
 
@@ -16,6 +20,19 @@ class ScaledFontFreetype : public ScaledFontBase
 public:
 
   ScaledFontFreetype(FontOptions* aFont, Float aSize);
+  ScaledFontFreetype(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex,
+                     Float aGlyphSize);
+  virtual ~ScaledFontFreetype();
+
+#ifdef USE_SKIA
+  virtual bool GetFontFileData(FontFileDataOutput aDataCallback, void *aBaton);
+
+private:
+  void InitFromData(const uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex);
+
+  // The face the typeface was loaded from, if it was loaded from data.
+  SkData *mFontData;
+#endif
 };
 
 }
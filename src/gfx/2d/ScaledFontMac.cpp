@@ -11,6 +11,7 @@
 #include "SkTypeface_mac.h"
 #endif
 #include "DrawTargetCG.h"
+#include "SFNTData.h"
 #include <vector>
 #include <dlfcn.h>
 
@@ -43,6 +44,11 @@ ScaledFontMac::ScaledFontMac(CGFontRef aFont, Float aSize)
   } else {
     mCTFont = nullptr;
   }
+#ifdef USE_SKIA
+  // Scaled fonts are shared between threads, so the typeface can't be
+  // created lazily on first use.
+  GetSkTypeface();
+#endif
 }
 
 ScaledFontMac::~ScaledFontMac()
@@ -101,5 +107,46 @@ ScaledFontMac::GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *aT
   }
 }
 
+bool
+ScaledFontMac::GetFontFileData(FontFileDataOutput aDataCallback, void *aBaton)
+{
+  // CoreGraphics doesn't keep the file a font came from, so put one back
+  // together from the font's tables.
+  CFArrayRef tags = CGFontCopyTableTags(mFont);
+  if (!tags) {
+    return false;
+  }
+
+  std::vector<CFDataRef> tableData;
+  std::vector<SFNTTable> tables;
+  CFIndex count = CFArrayGetCount(tags);
+  for (CFIndex i = 0; i < count; i++) {
+    // The tags are stored in the array as integers, not CF objects.
+    uint32_t tag = uint32_t(reinterpret_cast<uintptr_t>(CFArrayGetValueAtIndex(tags, i)));
+    CFDataRef data = CGFontCopyTableForTag(mFont, tag);
+    if (!data) {
+      continue;
+    }
+    tableData.push_back(data);
+    SFNTTable table = { tag, CFDataGetBytePtr(data), uint32_t(CFDataGetLength(data)) };
+    tables.push_back(table);
+  }
+  CFRelease(tags);
+
+  std::vector<uint8_t> font;
+  if (!tables.empty()) {
+    BuildSFNT(tables, font);
+  }
+  for (size_t i = 0; i < tableData.size(); i++) {
+    CFRelease(tableData[i]);
+  }
+
+  if (font.empty()) {
+    return false;
+  }
+  aDataCallback(&font.front(), uint32_t(font.size()), 0, mSize, aBaton);
+  return true;
+}
+
 }
 }
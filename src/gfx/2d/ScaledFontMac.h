@@ -25,6 +25,7 @@ public:
   virtual SkTypeface* GetSkTypeface();
 #endif
   virtual TemporaryRef<Path> GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *aTarget);
+  virtual bool GetFontFileData(FontFileDataOutput aDataCallback, void *aBaton);
 
 private:
   friend class DrawTargetCG;
@@ -44,6 +45,22 @@ public:
   static CTFontDrawGlyphsFuncT* CTFontDrawGlyphsPtr;
 };
 
+class GlyphRenderingOptionsCG : public GlyphRenderingOptions
+{
+public:
+  GlyphRenderingOptionsCG(bool aSubpixelPositioning)
+    : mSubpixelPositioning(aSubpixelPositioning)
+  {
+  }
+
+  virtual FontType GetType() const { return FONT_MAC; }
+
+  bool GetSubpixelPositioning() const { return mSubpixelPositioning; }
+
+private:
+  bool mSubpixelPositioning;
+};
+
 }
 }
 
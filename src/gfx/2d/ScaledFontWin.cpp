@@ -17,6 +17,11 @@ ScaledFontWin::ScaledFontWin(LOGFONT* aFont, Float aSize)
   : ScaledFontBase(aSize)
   , mLogFont(*aFont)
 {
+#ifdef USE_SKIA
+  // Scaled fonts are shared between threads, so the typeface can't be
+  // created lazily on first use.
+  GetSkTypeface();
+#endif
 }
 
 #ifdef USE_SKIA
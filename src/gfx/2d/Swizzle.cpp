@@ -0,0 +1,286 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "Swizzle.h"
+#include "2D.h"
+
+#include <string.h>
+#include <vector>
+
+namespace mozilla {
+namespace gfx {
+
+/* All conversions go through an intermediate row of 32-bit pixels with the
+ * bytes in B, G, R, A order, which is the memory layout of FORMAT_B8G8R8A8 on
+ * the little-endian platforms we build for. Each source row is unpacked into
+ * that layout, has its alpha fixed up, and is then packed into the
+ * destination format.
+ */
+static const int B = 0;
+static const int G = 1;
+static const int R = 2;
+static const int A = 3;
+
+static inline bool
+HasAlpha(PixelFormat aFormat)
+{
+  switch (aFormat) {
+  case PIXEL_FORMAT_B8G8R8X8:
+  case PIXEL_FORMAT_R5G6B5:
+  case PIXEL_FORMAT_R8G8B8X8:
+    return false;
+  default:
+    return true;
+  }
+}
+
+static inline bool
+IsPremultiplied(PixelFormat aFormat)
+{
+  return aFormat != PIXEL_FORMAT_B8G8R8A8_UNPREMULTIPLIED &&
+         aFormat != PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED;
+}
+
+static inline bool
+IsRedBlueSwapped(PixelFormat aFormat)
+{
+  return aFormat == PIXEL_FORMAT_R8G8B8A8 ||
+         aFormat == PIXEL_FORMAT_R8G8B8X8 ||
+         aFormat == PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED;
+}
+
+static inline bool
+IsValidPixelFormat(PixelFormat aFormat)
+{
+  return aFormat >= PIXEL_FORMAT_B8G8R8A8 &&
+         aFormat <= PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED;
+}
+
+// Computes (aValue * aAlpha) / 255 rounded to the nearest integer.
+static inline uint8_t
+MulDiv255(uint8_t aValue, uint8_t aAlpha)
+{
+  uint32_t t = uint32_t(aValue) * aAlpha + 128;
+  return uint8_t((t + (t >> 8)) >> 8);
+}
+
+static void
+PremultiplyRow_C(uint8_t *aRow, int32_t aWidth)
+{
+  for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+    uint8_t a = aRow[A];
+    aRow[B] = MulDiv255(aRow[B], a);
+    aRow[G] = MulDiv255(aRow[G], a);
+    aRow[R] = MulDiv255(aRow[R], a);
+  }
+}
+
+static void
+UnpremultiplyRow_C(uint8_t *aRow, int32_t aWidth)
+{
+  for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+    uint32_t a = aRow[A];
+    if (a == 0xFF) {
+      continue;
+    }
+    if (a == 0) {
+      aRow[B] = aRow[G] = aRow[R] = 0;
+      continue;
+    }
+    for (int c = B; c <= R; c++) {
+      // Values above the alpha are invalid premultiplied data; clamp them.
+      uint32_t v = (uint32_t(aRow[c]) * 0xFF + a / 2) / a;
+      aRow[c] = uint8_t(v > 0xFF ? 0xFF : v);
+    }
+  }
+}
+
+static void
+SwapRedBlueRow_C(uint8_t *aRow, int32_t aWidth)
+{
+  for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+    uint8_t t = aRow[B];
+    aRow[B] = aRow[R];
+    aRow[R] = t;
+  }
+}
+
+static void
+FillAlphaRow_C(uint8_t *aRow, int32_t aWidth)
+{
+  for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+    aRow[A] = 0xFF;
+  }
+}
+
+static void
+PremultiplyRow(uint8_t *aRow, int32_t aWidth)
+{
+#ifdef USE_SSE2
+  if (Factory::HasSSE2()) {
+    PremultiplyRow_SSE2(aRow, aWidth);
+  } else
+#endif
+  {
+    PremultiplyRow_C(aRow, aWidth);
+  }
+}
+
+static void
+UnpremultiplyRow(uint8_t *aRow, int32_t aWidth)
+{
+#ifdef USE_SSE2
+  if (Factory::HasSSE2()) {
+    UnpremultiplyRow_SSE2(aRow, aWidth);
+  } else
+#endif
+  {
+    UnpremultiplyRow_C(aRow, aWidth);
+  }
+}
+
+static void
+SwapRedBlueRow(uint8_t *aRow, int32_t aWidth)
+{
+#ifdef USE_SSE2
+  if (Factory::HasSSE2()) {
+    SwapRedBlueRow_SSE2(aRow, aWidth);
+  } else
+#endif
+  {
+    SwapRedBlueRow_C(aRow, aWidth);
+  }
+}
+
+static void
+FillAlphaRow(uint8_t *aRow, int32_t aWidth)
+{
+#ifdef USE_SSE2
+  if (Factory::HasSSE2()) {
+    FillAlphaRow_SSE2(aRow, aWidth);
+  } else
+#endif
+  {
+    FillAlphaRow_C(aRow, aWidth);
+  }
+}
+
+static void
+UnpackRow(const uint8_t *aSrc, PixelFormat aFormat, uint8_t *aRow, int32_t aWidth)
+{
+  switch (aFormat) {
+  case PIXEL_FORMAT_R5G6B5:
+    {
+      const uint16_t *src = reinterpret_cast<const uint16_t*>(aSrc);
+      for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+        uint16_t p = src[x];
+        uint8_t r = (p >> 11) & 0x1F;
+        uint8_t g = (p >> 5) & 0x3F;
+        uint8_t b = p & 0x1F;
+        // Replicate the high bits into the low ones so 0x1F maps to 0xFF.
+        aRow[R] = (r << 3) | (r >> 2);
+        aRow[G] = (g << 2) | (g >> 4);
+        aRow[B] = (b << 3) | (b >> 2);
+        aRow[A] = 0xFF;
+      }
+      return;
+    }
+  case PIXEL_FORMAT_A8:
+    for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+      aRow[B] = aRow[G] = aRow[R] = 0;
+      aRow[A] = aSrc[x];
+    }
+    return;
+  default:
+    break;
+  }
+
+  memmove(aRow, aSrc, aWidth * 4);
+  if (IsRedBlueSwapped(aFormat)) {
+    SwapRedBlueRow(aRow, aWidth);
+  }
+  if (!HasAlpha(aFormat)) {
+    FillAlphaRow(aRow, aWidth);
+  }
+}
+
+static void
+PackRow(uint8_t *aRow, PixelFormat aFormat, uint8_t *aDst, int32_t aWidth)
+{
+  switch (aFormat) {
+  case PIXEL_FORMAT_R5G6B5:
+    {
+      uint16_t *dst = reinterpret_cast<uint16_t*>(aDst);
+      for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+        dst[x] = uint16_t(((aRow[R] & 0xF8) << 8) |
+                          ((aRow[G] & 0xFC) << 3) |
+                          (aRow[B] >> 3));
+      }
+      return;
+    }
+  case PIXEL_FORMAT_A8:
+    for (int32_t x = 0; x < aWidth; x++, aRow += 4) {
+      aDst[x] = aRow[A];
+    }
+    return;
+  default:
+    break;
+  }
+
+  if (!HasAlpha(aFormat)) {
+    FillAlphaRow(aRow, aWidth);
+  }
+  if (IsRedBlueSwapped(aFormat)) {
+    SwapRedBlueRow(aRow, aWidth);
+  }
+  memmove(aDst, aRow, aWidth * 4);
+}
+
+bool
+ConvertPixels(const uint8_t *aSrc, int32_t aSrcStride, PixelFormat aSrcFormat,
+              uint8_t *aDst, int32_t aDstStride, PixelFormat aDstFormat,
+              const IntSize &aSize)
+{
+  if (!aSrc || !aDst || aSize.width < 0 || aSize.height < 0 ||
+      !IsValidPixelFormat(aSrcFormat) || !IsValidPixelFormat(aDstFormat) ||
+      aSrcStride < aSize.width * BytesPerPixel(aSrcFormat) ||
+      aDstStride < aSize.width * BytesPerPixel(aDstFormat)) {
+    return false;
+  }
+
+  if (aSrcFormat == aDstFormat) {
+    if (aSrc == aDst && aSrcStride == aDstStride) {
+      return true;
+    }
+    for (int32_t y = 0; y < aSize.height; y++) {
+      memmove(aDst + y * aDstStride, aSrc + y * aSrcStride,
+              aSize.width * BytesPerPixel(aSrcFormat));
+    }
+    return true;
+  }
+
+  bool premultiply = HasAlpha(aSrcFormat) && !IsPremultiplied(aSrcFormat) &&
+                     (IsPremultiplied(aDstFormat) || !HasAlpha(aDstFormat));
+  bool unpremultiply = HasAlpha(aSrcFormat) && IsPremultiplied(aSrcFormat) &&
+                       !IsPremultiplied(aDstFormat);
+
+  std::vector<uint8_t> row(aSize.width * 4);
+  for (int32_t y = 0; y < aSize.height; y++) {
+    if (!aSize.width) {
+      break;
+    }
+    UnpackRow(aSrc + y * aSrcStride, aSrcFormat, &row.front(), aSize.width);
+    if (premultiply) {
+      PremultiplyRow(&row.front(), aSize.width);
+    } else if (unpremultiply) {
+      UnpremultiplyRow(&row.front(), aSize.width);
+    }
+    PackRow(&row.front(), aDstFormat, aDst + y * aDstStride, aSize.width);
+  }
+  return true;
+}
+
+}
+}
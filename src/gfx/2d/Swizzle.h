@@ -0,0 +1,67 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#ifndef MOZILLA_GFX_SWIZZLE_H_
+#define MOZILLA_GFX_SWIZZLE_H_
+
+#include "Types.h"
+#include "Point.h"
+
+namespace mozilla {
+namespace gfx {
+
+/* The pixel layouts understood by ConvertPixels. The first four values match
+ * SurfaceFormat and describe Azure's own native-endian, premultiplied
+ * layouts. The remaining ones describe the byte-ordered buffers that image
+ * decoders and GL deal in, with either premultiplied or straight alpha.
+ */
+enum PixelFormat
+{
+  PIXEL_FORMAT_B8G8R8A8,
+  PIXEL_FORMAT_B8G8R8X8,
+  PIXEL_FORMAT_R5G6B5,
+  PIXEL_FORMAT_A8,
+  PIXEL_FORMAT_R8G8B8A8,
+  PIXEL_FORMAT_R8G8B8X8,
+  PIXEL_FORMAT_B8G8R8A8_UNPREMULTIPLIED,
+  PIXEL_FORMAT_R8G8B8A8_UNPREMULTIPLIED
+};
+
+static inline int
+BytesPerPixel(PixelFormat aFormat)
+{
+  switch (aFormat) {
+  case PIXEL_FORMAT_A8:
+    return 1;
+  case PIXEL_FORMAT_R5G6B5:
+    return 2;
+  default:
+    return 4;
+  }
+}
+
+/* Converts aSize pixels from aSrc in aSrcFormat to aDst in aDstFormat,
+ * premultiplying, unpremultiplying, swizzling and (un)packing R5G6B5 as
+ * required. aSrc and aDst may be the same buffer as long as both formats have
+ * the same number of bytes per pixel and both strides are equal. Converting
+ * to a format without alpha drops the alpha channel, which for premultiplied
+ * input amounts to compositing over black. Converting to A8 keeps only the
+ * alpha channel. Returns false if the arguments are invalid.
+ */
+bool ConvertPixels(const uint8_t *aSrc, int32_t aSrcStride, PixelFormat aSrcFormat,
+                   uint8_t *aDst, int32_t aDstStride, PixelFormat aDstFormat,
+                   const IntSize &aSize);
+
+// Row helpers operating on 32-bit pixels. The SSE2 versions live in
+// SwizzleSSE2.cpp and are only used when Factory::HasSSE2() is true.
+void PremultiplyRow_SSE2(uint8_t *aRow, int32_t aWidth);
+void UnpremultiplyRow_SSE2(uint8_t *aRow, int32_t aWidth);
+void SwapRedBlueRow_SSE2(uint8_t *aRow, int32_t aWidth);
+void FillAlphaRow_SSE2(uint8_t *aRow, int32_t aWidth);
+
+}
+}
+
+#endif /* MOZILLA_GFX_SWIZZLE_H_ */
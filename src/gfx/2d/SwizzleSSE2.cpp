@@ -0,0 +1,162 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "Swizzle.h"
+#include "mozilla/Attributes.h"
+
+#include "SSEHelpers.h"
+
+/* These functions work on four B8G8R8A8 pixels at a time and fall back to
+ * plain C for the remainder of the row. Rows have no alignment guarantees,
+ * so all loads and stores are unaligned.
+ */
+
+namespace mozilla {
+namespace gfx {
+
+// Computes (aValue * aAlpha) / 255 for eight 16-bit lanes, rounded to the
+// nearest integer, using the same (t + (t >> 8)) >> 8 trick as the C version.
+MOZ_ALWAYS_INLINE __m128i MulDiv255_SSE2(__m128i aValue, __m128i aAlpha)
+{
+  __m128i t = _mm_add_epi16(_mm_mullo_epi16(aValue, aAlpha), _mm_set1_epi16(128));
+  return _mm_srli_epi16(_mm_add_epi16(t, _mm_srli_epi16(t, 8)), 8);
+}
+
+void
+PremultiplyRow_SSE2(uint8_t *aRow, int32_t aWidth)
+{
+  __m128i zero = _mm_setzero_si128();
+  __m128i alphaMask = _mm_set1_epi32(0xFF000000);
+
+  int32_t x = 0;
+  for (; x + 4 <= aWidth; x += 4) {
+    __m128i *p = reinterpret_cast<__m128i*>(aRow + x * 4);
+    __m128i pixels = loadUnaligned128(p);
+
+    __m128i lo = _mm_unpacklo_epi8(pixels, zero);
+    __m128i hi = _mm_unpackhi_epi8(pixels, zero);
+
+    // Broadcast each pixel's alpha into all four of its 16-bit lanes.
+    __m128i loAlpha = _mm_shufflehi_epi16(_mm_shufflelo_epi16(lo, _MM_SHUFFLE(3, 3, 3, 3)),
+                                          _MM_SHUFFLE(3, 3, 3, 3));
+    __m128i hiAlpha = _mm_shufflehi_epi16(_mm_shufflelo_epi16(hi, _MM_SHUFFLE(3, 3, 3, 3)),
+                                          _MM_SHUFFLE(3, 3, 3, 3));
+
+    lo = MulDiv255_SSE2(lo, loAlpha);
+    hi = MulDiv255_SSE2(hi, hiAlpha);
+
+    __m128i result = _mm_packus_epi16(lo, hi);
+    // Multiplying alpha by itself is wrong, so put the original back.
+    result = _mm_or_si128(_mm_andnot_si128(alphaMask, result),
+                          _mm_and_si128(alphaMask, pixels));
+    _mm_storeu_si128(p, result);
+  }
+
+  for (uint8_t *pixel = aRow + x * 4; x < aWidth; x++, pixel += 4) {
+    uint32_t a = pixel[3];
+    for (int c = 0; c < 3; c++) {
+      uint32_t t = pixel[c] * a + 128;
+      pixel[c] = uint8_t((t + (t >> 8)) >> 8);
+    }
+  }
+}
+
+// Computes aNumerator / aDenominator for four 32-bit lanes, rounded down. The
+// lanes hold values below 2^16, whose quotients single precision division
+// never rounds up to the next integer.
+MOZ_ALWAYS_INLINE __m128i Divide_SSE2(__m128i aNumerator, __m128i aDenominator)
+{
+  return _mm_cvttps_epi32(_mm_div_ps(_mm_cvtepi32_ps(aNumerator),
+                                     _mm_cvtepi32_ps(aDenominator)));
+}
+
+void
+UnpremultiplyRow_SSE2(uint8_t *aRow, int32_t aWidth)
+{
+  __m128i zero = _mm_setzero_si128();
+  __m128i alphaMask = _mm_set1_epi32(0xFF000000);
+  __m128i max = _mm_set1_epi16(0xFF);
+
+  int32_t x = 0;
+  for (; x + 4 <= aWidth; x += 4) {
+    __m128i *p = reinterpret_cast<__m128i*>(aRow + x * 4);
+    __m128i pixels = loadUnaligned128(p);
+
+    __m128i lo = _mm_unpacklo_epi8(pixels, zero);
+    __m128i hi = _mm_unpackhi_epi8(pixels, zero);
+    __m128i loAlpha = _mm_shufflehi_epi16(_mm_shufflelo_epi16(lo, _MM_SHUFFLE(3, 3, 3, 3)),
+                                          _MM_SHUFFLE(3, 3, 3, 3));
+    __m128i hiAlpha = _mm_shufflehi_epi16(_mm_shufflelo_epi16(hi, _MM_SHUFFLE(3, 3, 3, 3)),
+                                          _MM_SHUFFLE(3, 3, 3, 3));
+
+    // The C version's (v * 255 + a / 2) / a, whose numerator fits in 16 bits.
+    lo = _mm_add_epi16(_mm_mullo_epi16(lo, max), _mm_srli_epi16(loAlpha, 1));
+    hi = _mm_add_epi16(_mm_mullo_epi16(hi, max), _mm_srli_epi16(hiAlpha, 1));
+
+    __m128i q0 = Divide_SSE2(_mm_unpacklo_epi16(lo, zero), _mm_unpacklo_epi16(loAlpha, zero));
+    __m128i q1 = Divide_SSE2(_mm_unpackhi_epi16(lo, zero), _mm_unpackhi_epi16(loAlpha, zero));
+    __m128i q2 = Divide_SSE2(_mm_unpacklo_epi16(hi, zero), _mm_unpacklo_epi16(hiAlpha, zero));
+    __m128i q3 = Divide_SSE2(_mm_unpackhi_epi16(hi, zero), _mm_unpackhi_epi16(hiAlpha, zero));
+
+    // Packing saturates, which clamps values above the alpha to 255.
+    __m128i result = _mm_packus_epi16(_mm_packs_epi32(q0, q1), _mm_packs_epi32(q2, q3));
+    result = _mm_or_si128(_mm_andnot_si128(alphaMask, result),
+                          _mm_and_si128(alphaMask, pixels));
+    // Transparent pixels divided by zero above; make them transparent black.
+    __m128i transparent = _mm_cmpeq_epi32(_mm_and_si128(pixels, alphaMask), zero);
+    _mm_storeu_si128(p, _mm_andnot_si128(transparent, result));
+  }
+
+  for (uint8_t *pixel = aRow + x * 4; x < aWidth; x++, pixel += 4) {
+    uint32_t a = pixel[3];
+    for (int c = 0; c < 3; c++) {
+      uint32_t v = a ? (uint32_t(pixel[c]) * 0xFF + a / 2) / a : 0;
+      pixel[c] = uint8_t(v > 0xFF ? 0xFF : v);
+    }
+  }
+}
+
+void
+SwapRedBlueRow_SSE2(uint8_t *aRow, int32_t aWidth)
+{
+  __m128i greenAlphaMask = _mm_set1_epi32(0xFF00FF00);
+
+  int32_t x = 0;
+  for (; x + 4 <= aWidth; x += 4) {
+    __m128i *p = reinterpret_cast<__m128i*>(aRow + x * 4);
+    __m128i pixels = loadUnaligned128(p);
+
+    __m128i ga = _mm_and_si128(pixels, greenAlphaMask);
+    __m128i rb = _mm_andnot_si128(greenAlphaMask, pixels);
+    rb = _mm_or_si128(_mm_slli_epi32(rb, 16), _mm_srli_epi32(rb, 16));
+
+    _mm_storeu_si128(p, _mm_or_si128(ga, rb));
+  }
+
+  for (uint8_t *pixel = aRow + x * 4; x < aWidth; x++, pixel += 4) {
+    uint8_t t = pixel[0];
+    pixel[0] = pixel[2];
+    pixel[2] = t;
+  }
+}
+
+void
+FillAlphaRow_SSE2(uint8_t *aRow, int32_t aWidth)
+{
+  __m128i alphaMask = _mm_set1_epi32(0xFF000000);
+
+  int32_t x = 0;
+  for (; x + 4 <= aWidth; x += 4) {
+    __m128i *p = reinterpret_cast<__m128i*>(aRow + x * 4);
+    _mm_storeu_si128(p, _mm_or_si128(loadUnaligned128(p), alphaMask));
+  }
+
+  for (uint8_t *pixel = aRow + x * 4; x < aWidth; x++, pixel += 4) {
+    pixel[3] = 0xFF;
+  }
+}
+
+}
+}
@@ -8,3 +8,1166 @@ fn sanity_check() {
 
     unsafe { AzSanityCheck() };
 }
+
+#[test]
+fn convert_pixels_swizzles_and_premultiplies() {
+    use azure_hl::{convert_pixels, StraightRGBA, PremultipliedBGRA};
+    use geom::size::Size2D;
+
+    let size = Size2D { width: 2, height: 1 };
+    let straight = vec!(0x10u8, 0x20, 0x30, 0xff, 0xff, 0x80, 0x00, 0x80);
+    let mut premultiplied = Vec::from_elem(8, 0u8);
+    convert_pixels(straight.as_slice(), 8, StraightRGBA,
+                   premultiplied.as_mut_slice(), 8, PremultipliedBGRA,
+                   size);
+    assert_eq!(premultiplied, vec!(0x30u8, 0x20, 0x10, 0xff, 0x00, 0x40, 0x80, 0x80));
+}
+
+// The SSE2 conversions work on blocks of four pixels and finish each row in plain C, so the
+// conversion tests below use rows of more than four pixels to run both.
+
+#[test]
+fn premultiply_matches_the_scalar_formula_for_every_value_and_alpha() {
+    use azure_hl::premultiply;
+    use geom::size::Size2D;
+
+    // Row `a` holds every value, in each of the three color channels, with alpha `a`. Each row
+    // is 259 pixels wide: 64 blocks of four and a tail of three.
+    let (width, height) = (259u, 256u);
+    let mut pixels = Vec::from_elem(width * height * 4, 0u8);
+    for a in range(0, height) {
+        for x in range(0, width) {
+            let offset = (a * width + x) * 4;
+            *pixels.get_mut(offset) = (x % 256) as u8;
+            *pixels.get_mut(offset + 1) = ((x + 85) % 256) as u8;
+            *pixels.get_mut(offset + 2) = ((x + 170) % 256) as u8;
+            *pixels.get_mut(offset + 3) = a as u8;
+        }
+    }
+    let original = pixels.clone();
+    premultiply(pixels.as_mut_slice(), (width * 4) as i32, Size2D(width as i32, height as i32));
+
+    for (before, after) in original.as_slice().chunks(4).zip(pixels.as_slice().chunks(4)) {
+        let a = before[3] as u32;
+        for c in range(0, 3u) {
+            let t = before[c] as u32 * a + 128;
+            assert_eq!(after[c] as u32, (t + (t >> 8)) >> 8);
+        }
+        assert_eq!(after[3], before[3]);
+    }
+}
+
+#[test]
+fn unpremultiply_matches_the_scalar_formula_for_every_value_and_alpha() {
+    use azure_hl::unpremultiply;
+    use geom::size::Size2D;
+    use std::cmp;
+
+    // As above, including values above the alpha, which are invalid and get clamped.
+    let (width, height) = (259u, 256u);
+    let mut pixels = Vec::from_elem(width * height * 4, 0u8);
+    for a in range(0, height) {
+        for x in range(0, width) {
+            let offset = (a * width + x) * 4;
+            *pixels.get_mut(offset) = (x % 256) as u8;
+            *pixels.get_mut(offset + 1) = ((x + 85) % 256) as u8;
+            *pixels.get_mut(offset + 2) = ((x + 170) % 256) as u8;
+            *pixels.get_mut(offset + 3) = a as u8;
+        }
+    }
+    let original = pixels.clone();
+    unpremultiply(pixels.as_mut_slice(), (width * 4) as i32, Size2D(width as i32, height as i32));
+
+    for (before, after) in original.as_slice().chunks(4).zip(pixels.as_slice().chunks(4)) {
+        let a = before[3] as u32;
+        for c in range(0, 3u) {
+            let expected = match a {
+                0 => 0,
+                0xff => before[c] as u32,
+                _ => cmp::min((before[c] as u32 * 0xff + a / 2) / a, 0xff),
+            };
+            assert_eq!(after[c] as u32, expected);
+        }
+        assert_eq!(after[3], before[3]);
+    }
+}
+
+#[test]
+fn convert_pixels_swaps_and_fills_whole_rows() {
+    use azure_hl::{convert_pixels, PremultipliedBGRA, PremultipliedRGBA, OpaqueBGRX};
+    use geom::size::Size2D;
+
+    let mut bgra = Vec::new();
+    for i in range(0, 7u8) {
+        bgra.push_all([i, 0x10 + i, 0x20 + i, 0x30 + i]);
+    }
+
+    let mut rgba = Vec::from_elem(7 * 4, 0u8);
+    convert_pixels(bgra.as_slice(), 7 * 4, PremultipliedBGRA,
+                   rgba.as_mut_slice(), 7 * 4, PremultipliedRGBA,
+                   Size2D(7, 1));
+    for (i, pixel) in rgba.as_slice().chunks(4).enumerate() {
+        let i = i as u8;
+        assert_eq!(pixel, [0x20 + i, 0x10 + i, i, 0x30 + i].as_slice());
+    }
+
+    // Formats without alpha read and write it as opaque.
+    let mut bgrx = Vec::from_elem(7 * 4, 0u8);
+    convert_pixels(bgra.as_slice(), 7 * 4, OpaqueBGRX,
+                   bgrx.as_mut_slice(), 7 * 4, PremultipliedBGRA,
+                   Size2D(7, 1));
+    for (i, pixel) in bgrx.as_slice().chunks(4).enumerate() {
+        let i = i as u8;
+        assert_eq!(pixel, [i, 0x10 + i, 0x20 + i, 0xff].as_slice());
+    }
+}
+
+#[test]
+fn convert_pixels_packs_and_unpacks_rgb565() {
+    use azure_hl::{convert_pixels, PremultipliedBGRA, PackedRGB565};
+    use geom::size::Size2D;
+
+    let mut bgra = Vec::new();
+    for _ in range(0, 5u) {
+        bgra.push_all([0x56u8, 0x34, 0x12, 0xff]);
+    }
+    let mut packed = Vec::from_elem(5 * 2, 0u8);
+    convert_pixels(bgra.as_slice(), 5 * 4, PremultipliedBGRA,
+                   packed.as_mut_slice(), 5 * 2, PackedRGB565,
+                   Size2D(5, 1));
+    // Red 0x12 keeps 5 bits, green 0x34 6 bits and blue 0x56 5 bits: 0x11aa, stored
+    // little-endian.
+    for pixel in packed.as_slice().chunks(2) {
+        assert_eq!(pixel, [0xaau8, 0x11].as_slice());
+    }
+
+    // Unpacking replicates the high bits into the low ones, so 0x1f comes back as 0xff.
+    let mut unpacked = Vec::from_elem(5 * 4, 0u8);
+    convert_pixels(packed.as_slice(), 5 * 2, PackedRGB565,
+                   unpacked.as_mut_slice(), 5 * 4, PremultipliedBGRA,
+                   Size2D(5, 1));
+    for pixel in unpacked.as_slice().chunks(4) {
+        assert_eq!(pixel, [0x52u8, 0x34, 0x10, 0xff].as_slice());
+    }
+    let white = [0xffu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    convert_pixels(white, 5 * 2, PackedRGB565,
+                   unpacked.as_mut_slice(), 5 * 4, PremultipliedBGRA,
+                   Size2D(5, 1));
+    assert!(unpacked.iter().all(|&byte| byte == 0xff));
+}
+
+#[test]
+fn convert_pixels_extracts_and_expands_alpha_masks() {
+    use azure_hl::{convert_pixels, PremultipliedBGRA, AlphaMask};
+    use geom::size::Size2D;
+
+    let mut bgra = Vec::new();
+    for &alpha in [0x00u8, 0x40, 0x80, 0xc0, 0xff].iter() {
+        bgra.push_all([0x00, 0x00, 0x00, alpha]);
+    }
+    let mut mask = Vec::from_elem(5, 0u8);
+    convert_pixels(bgra.as_slice(), 5 * 4, PremultipliedBGRA,
+                   mask.as_mut_slice(), 5, AlphaMask,
+                   Size2D(5, 1));
+    assert_eq!(mask, vec!(0x00u8, 0x40, 0x80, 0xc0, 0xff));
+
+    let mut expanded = Vec::from_elem(5 * 4, 0xabu8);
+    convert_pixels(mask.as_slice(), 5, AlphaMask,
+                   expanded.as_mut_slice(), 5 * 4, PremultipliedBGRA,
+                   Size2D(5, 1));
+    for (pixel, &alpha) in expanded.as_slice().chunks(4).zip(mask.iter()) {
+        assert_eq!(pixel, [0x00u8, 0x00, 0x00, alpha].as_slice());
+    }
+}
+
+#[test]
+fn data_source_surfaces_convert_to_other_formats() {
+    use azure_hl::{DataSourceSurface, B8G8R8A8, StraightRGBA};
+    use geom::size::Size2D;
+
+    // Five premultiplied half-transparent pixels, in a surface whose stride may be padded.
+    let surface = DataSourceSurface::new(Size2D(5i32, 2i32), B8G8R8A8);
+    let stride = surface.stride() as uint;
+    surface.with_mut_data(|data| {
+        for y in range(0, 2u) {
+            for x in range(0, 5u) {
+                let offset = y * stride + x * 4;
+                data.mut_slice(offset, offset + 4).copy_from([0x40u8, 0x20, 0x10, 0x80]);
+            }
+        }
+    });
+
+    let pixels = surface.convert_to(StraightRGBA);
+    assert_eq!(pixels.len(), 5 * 2 * 4);
+    for pixel in pixels.as_slice().chunks(4) {
+        assert_eq!(pixel, [0x20u8, 0x40, 0x80, 0x80].as_slice());
+    }
+}
+
+#[test]
+fn png_round_trip() {
+    use azure_hl::{DataSourceSurface, B8G8R8A8};
+    use geom::size::Size2D;
+    use std::io::MemWriter;
+
+    let surface = DataSourceSurface::new(Size2D { width: 2, height: 1 }, B8G8R8A8);
+    surface.with_mut_data(|data| {
+        let pixels = [0x00u8, 0x40, 0x80, 0x80, 0x30, 0x20, 0x10, 0xff];
+        for (dst, src) in data.mut_iter().zip(pixels.iter()) {
+            *dst = *src;
+        }
+    });
+
+    let mut writer = MemWriter::new();
+    surface.write_png(&mut writer).unwrap();
+    let decoded = DataSourceSurface::from_png(writer.get_ref()).unwrap();
+    decoded.with_data(|data| {
+        assert_eq!(data, [0x00u8, 0x40, 0x80, 0x80, 0x30, 0x20, 0x10, 0xff].as_slice());
+    });
+}
+
+#[test]
+fn png_transparency_chunk_makes_gray_pixels_transparent() {
+    use azure_hl::{DataSourceSurface, SourceSurfaceMethods, B8G8R8A8};
+
+    // A 2x1 8-bit grayscale image with pixels 0x10 and 0x80, whose tRNS chunk makes 0x10
+    // transparent.
+    let data = [
+        0x89u8, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a,
+        0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x02,
+        0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0xd1, 0x49, 0x20, 0x56,
+        0x00, 0x00, 0x00, 0x02, 0x74, 0x52, 0x4e, 0x53, 0x00, 0x10, 0x6b, 0x24, 0xdd, 0x5c,
+        0x00, 0x00, 0x00, 0x0b, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x10, 0x68,
+        0x00, 0x00, 0x00, 0xa3, 0x00, 0x91, 0x0e, 0x65, 0x2b, 0xb0,
+        0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+    let surface = DataSourceSurface::from_png(data).unwrap();
+    assert!(surface.format() == B8G8R8A8);
+    surface.with_data(|data| {
+        assert_eq!(data.slice_to(8), [0x00u8, 0x00, 0x00, 0x00, 0x80, 0x80, 0x80, 0xff].as_slice());
+    });
+}
+
+#[test]
+fn memory_recorder_captures_draw_calls() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8};
+    use azure_hl::{Color, ColorPattern};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let recorder = DrawEventRecorder::new_memory();
+    let header = recorder.take_recorded_data().unwrap();
+    assert_eq!(header.as_slice(), [0xedu8, 0xfe, 0x01, 0xc0, 0x03, 0x00, 0x00, 0x00].as_slice());
+
+    let target = DrawTarget::new(SkiaBackend, Size2D { width: 16, height: 16 }, B8G8R8A8);
+    let recording = DrawTarget::new_recording(&recorder, &target);
+    recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(8.0, 8.0)),
+                        &ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0)),
+                        None);
+
+    // Taking the data empties the recorder, so only the new events come back.
+    let events = recorder.take_recorded_data().unwrap();
+    assert!(events.len() > 0);
+    assert!(events.slice_to(4) != header.slice_to(4));
+    assert_eq!(recorder.take_recorded_data().unwrap().len(), 0);
+}
+
+#[test]
+fn replay_reproduces_recorded_drawing() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA};
+    use azure_hl::{Color, ColorPattern, MalformedRecording, replay};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+    use recording::BadHeader;
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(4.0, 4.0)),
+                            &ColorPattern::new(Color::new(0.0, 0.0, 1.0, 1.0)),
+                            None);
+    }
+
+    let targets = replay(recorder.take_recorded_data().unwrap().as_slice(), SkiaBackend).unwrap();
+    assert_eq!(targets.len(), 1);
+    let size = targets.get(0).get_size();
+    assert_eq!((size.width, size.height), (4, 4));
+    let pixels = targets.get(0).snapshot().get_data_surface().convert_to(StraightRGBA);
+    assert_eq!(pixels.slice_to(4), [0x00u8, 0x00, 0xff, 0xff].as_slice());
+
+    assert_eq!(replay([1u8, 2, 3], SkiaBackend).err(), Some(MalformedRecording(BadHeader)));
+}
+
+#[test]
+fn file_recorder_writes_a_replayable_recording() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA};
+    use azure_hl::{Color, ColorPattern, replay};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+    use std::io::{File, TempDir};
+
+    let directory = TempDir::new("azure-test").unwrap();
+    let path = directory.path().join("recording.aer");
+    {
+        let recorder = DrawEventRecorder::for_file(path.as_str().unwrap()).unwrap();
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(4.0, 4.0)),
+                            &ColorPattern::new(Color::new(0.0, 1.0, 0.0, 1.0)),
+                            None);
+        // File recorders have nothing to hand out; the recording only goes to the file.
+        assert!(recorder.take_recorded_data().is_none());
+    }
+
+    // The recorder and the recording target are gone, so the file is complete.
+    let data = File::open(&path).read_to_end().unwrap();
+    let targets = replay(data.as_slice(), SkiaBackend).unwrap();
+    assert_eq!(targets.len(), 1);
+    let pixels = targets.get(0).snapshot().get_data_surface().convert_to(StraightRGBA);
+    assert_eq!(pixels.len(), 4 * 4 * 4);
+    for pixel in pixels.as_slice().chunks(4) {
+        assert_eq!(pixel, [0x00u8, 0xff, 0x00, 0xff].as_slice());
+    }
+
+    let missing = directory.path().join("missing").join("recording.aer");
+    assert!(DrawEventRecorder::for_file(missing.as_str().unwrap()).is_none());
+}
+
+#[test]
+fn recordings_are_little_endian_and_versioned() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8, MalformedRecording};
+    use azure_hl::replay;
+    use recording::{RecordedEvents, UnsupportedVersion};
+    use geom::size::Size2D;
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        DrawTarget::new_recording(&recorder, &target);
+    }
+    let data = recorder.take_recorded_data().unwrap();
+    assert_eq!(data.slice_to(8), [0xedu8, 0xfe, 0x01, 0xc0, 0x03, 0x00, 0x00, 0x00].as_slice());
+    assert!(replay(data.as_slice(), SkiaBackend).is_ok());
+
+    let mut old_version = data.clone();
+    *old_version.get_mut(4) = 0x02;
+    assert_eq!(replay(old_version.as_slice(), SkiaBackend).err(),
+               Some(MalformedRecording(UnsupportedVersion)));
+
+    let mut unknown_event = data.clone();
+    unknown_event.push_all([0xffu8, 0x00, 0x00, 0x00]);
+    let mut events = RecordedEvents::new(unknown_event.as_slice());
+    while events.next().is_some() {}
+    assert!(events.failed());
+}
+
+#[test]
+fn recorded_events_reject_malformed_recordings() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8};
+    use azure_hl::{Color, ColorPattern};
+    use recording::{RecordedEvents, RecordingError, BadHeader, UnknownEvent, TruncatedEvent};
+    use recording::InvalidEvent;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    fn error_for(data: &[u8]) -> Option<RecordingError> {
+        let mut events = RecordedEvents::new(data);
+        while events.next().is_some() {}
+        events.error()
+    }
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(4.0, 4.0)),
+                            &ColorPattern::new(Color::new(0.0, 0.0, 1.0, 1.0)),
+                            None);
+    }
+    let data = recorder.take_recorded_data().unwrap();
+    assert_eq!(error_for(data.as_slice()), None);
+    for length in range(0, data.len()) {
+        let error = error_for(data.slice_to(length));
+        if length < 8 {
+            assert_eq!(error, Some(BadHeader));
+        } else {
+            assert!(error == None || error == Some(TruncatedEvent));
+        }
+    }
+
+    let header = [0xedu8, 0xfe, 0x01, 0xc0, 0x03, 0x00, 0x00, 0x00];
+    let with_header = |event: &[u8]| {
+        let mut recording = Vec::from_slice(header.as_slice());
+        recording.push_all(event);
+        error_for(recording.as_slice())
+    };
+
+    // Gradient stops claiming four billion stops.
+    assert_eq!(with_header([21u8, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0,
+                            0xff, 0xff, 0xff, 0xff]),
+               Some(TruncatedEvent));
+    // A path with an unknown op.
+    assert_eq!(with_header([17u8, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0,
+                            0, 0, 0, 0,  7, 0, 0, 0]),
+               Some(InvalidEvent));
+    // A source surface with a negative width.
+    assert_eq!(with_header([19u8, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0,  0xff, 0xff, 0xff, 0xff,
+                            1, 0, 0, 0,  0, 0, 0, 0]),
+               Some(InvalidEvent));
+    assert_eq!(with_header([0x7fu8, 0, 0, 0]), Some(UnknownEvent));
+}
+
+#[test]
+fn players_read_recordings_from_readers() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA};
+    use azure_hl::{Color, ColorPattern};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+    use recording::{DrawEventPlayer, TruncatedEvent};
+    use std::io::MemReader;
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(4.0, 4.0)),
+                            &ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0)),
+                            None);
+    }
+    let data = recorder.take_recorded_data().unwrap();
+
+    let mut player = DrawEventPlayer::new(SkiaBackend);
+    assert!(player.play_from(&mut MemReader::new(data.clone())));
+    let targets = player.draw_targets();
+    assert_eq!(targets.len(), 1);
+    let pixels = targets.get(0).snapshot().get_data_surface().convert_to(StraightRGBA);
+    assert_eq!(pixels.slice_to(4), [0xffu8, 0x00, 0x00, 0xff].as_slice());
+
+    // Readers can't be seeked in to check lengths up front, so a truncated recording is only
+    // noticed once the reader runs out.
+    let mut truncated = data.clone();
+    truncated.pop();
+    let mut player = DrawEventPlayer::new(SkiaBackend);
+    assert!(!player.play_from(&mut MemReader::new(truncated)));
+    assert_eq!(player.error(), Some(TruncatedEvent));
+}
+
+#[test]
+fn playback_rejects_oversized_draw_targets() {
+    use azure_hl::{SkiaBackend, MalformedRecording, replay};
+    use recording::InvalidEvent;
+
+    // A DrawTargetCreation event for a 16385 by 1 pixel Skia draw target, one pixel wider than
+    // recordings may create.
+    let recording = [0xedu8, 0xfe, 0x01, 0xc0, 0x03, 0x00, 0x00, 0x00,
+                     0, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0,  5, 0, 0, 0,
+                     0x01, 0x40, 0, 0,  1, 0, 0, 0,  0, 0, 0, 0];
+    assert_eq!(replay(recording, SkiaBackend).err(), Some(MalformedRecording(InvalidEvent)));
+}
+
+#[test]
+fn playback_rejects_unknown_references() {
+    use azure_hl::{SkiaBackend, MalformedRecording, replay};
+    use recording::{DrawEventPlayer, UnknownReference};
+
+    let header = [0xedu8, 0xfe, 0x01, 0xc0, 0x03, 0x00, 0x00, 0x00];
+    let play = |event: &[u8]| {
+        let mut recording = Vec::from_slice(header.as_slice());
+        recording.push_all(event);
+        let mut player = DrawEventPlayer::new(SkiaBackend);
+        assert!(!player.play(recording.as_slice()));
+        let error = player.error();
+        assert_eq!(replay(recording.as_slice(), SkiaBackend).err(),
+                   error.map(|error| MalformedRecording(error)));
+        error
+    };
+
+    // A PopClip on a draw target that was never created.
+    assert_eq!(play([10u8, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0]), Some(UnknownReference));
+    // A snapshot of a draw target that was never created.
+    assert_eq!(play([23u8, 0, 0, 0,  2, 0, 0, 0, 0, 0, 0, 0,  1, 0, 0, 0, 0, 0, 0, 0]),
+               Some(UnknownReference));
+}
+
+#[test]
+fn recorded_events_describe_draw_calls() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8};
+    use azure_hl::{Color, ColorPattern};
+    use recording::{RecordedEvents, DrawTargetCreation, FillRect, DrawTargetDestruction};
+    use recording::ColorPatternKind;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 4, height: 4 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(1.0, 1.0), Size2D(2.0, 2.0)),
+                            &ColorPattern::new(Color::new(0.0, 1.0, 0.0, 1.0)),
+                            None);
+    }
+
+    let data = recorder.take_recorded_data().unwrap();
+    let mut events = RecordedEvents::new(data.as_slice());
+    let dt = match events.next() {
+        Some(DrawTargetCreation(dt, SkiaBackend, size, B8G8R8A8)) => {
+            assert_eq!((size.width, size.height), (4, 4));
+            dt
+        }
+        _ => fail!("expected a draw target creation"),
+    };
+    match events.next() {
+        Some(FillRect(target, rect, ColorPatternKind(color))) => {
+            assert_eq!(target, dt);
+            assert_eq!(rect, Rect(Point2D(1.0, 1.0), Size2D(2.0, 2.0)));
+            assert_eq!((color.r, color.g, color.b, color.a), (0.0, 1.0, 0.0, 1.0));
+        }
+        _ => fail!("expected a fill rect"),
+    }
+    match events.next() {
+        Some(DrawTargetDestruction(target)) => assert_eq!(target, dt),
+        _ => fail!("expected a draw target destruction"),
+    }
+    assert!(events.next().is_none());
+    assert!(!events.failed());
+
+    let mut truncated = RecordedEvents::new(data.slice_to(data.len() - 1));
+    assert_eq!(truncated.by_ref().count(), 2);
+    assert!(truncated.failed());
+}
+
+#[test]
+fn player_steps_through_recording() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA};
+    use azure_hl::{Color, ColorPattern};
+    use recording::{DrawEventPlayer, RecordedEvents};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let recorder = DrawEventRecorder::new_memory();
+    {
+        let target = DrawTarget::new(SkiaBackend, Size2D { width: 2, height: 2 }, B8G8R8A8);
+        let recording = DrawTarget::new_recording(&recorder, &target);
+        recording.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(2.0, 2.0)),
+                            &ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0)),
+                            None);
+    }
+    let data = recorder.take_recorded_data().unwrap();
+
+    let mut events = RecordedEvents::new(data.as_slice());
+    let mut player = DrawEventPlayer::new(SkiaBackend);
+    assert!(!player.play_current(&events));
+
+    assert!(events.next().is_some());
+    assert!(events.current_info().as_slice().contains("DrawTarget Creation"));
+    assert!(player.play_current(&events));
+    let pixels = player.draw_targets().get(0).snapshot().get_data_surface().convert_to(StraightRGBA);
+    assert_eq!(pixels.slice_to(4), [0x00u8, 0x00, 0x00, 0x00].as_slice());
+
+    assert!(events.next().is_some());
+    assert!(events.current_info().as_slice().contains("FillRect"));
+    assert!(player.play_current(&events));
+    let pixels = player.draw_targets().get(0).snapshot().get_data_surface().convert_to(StraightRGBA);
+    assert_eq!(pixels.slice_to(4), [0xffu8, 0x00, 0x00, 0xff].as_slice());
+}
+
+#[test]
+fn alpha_box_blur_spreads_coverage() {
+    use blur::AlphaBoxBlur;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let radius = AlphaBoxBlur::calculate_blur_radius(Point2D(2.0, 2.0));
+    assert!(radius.width > 0);
+    assert_eq!(radius.width, radius.height);
+
+    let mut blur = AlphaBoxBlur::new(&Rect(Point2D(10.0, 10.0), Size2D(4.0, 4.0)),
+                                     Size2D(0, 0),
+                                     radius,
+                                     None,
+                                     None);
+    let rect = blur.rect();
+    assert_eq!((rect.origin.x, rect.origin.y), (10 - radius.width, 10 - radius.height));
+    assert_eq!(rect.size.width, 4 + 2 * radius.width);
+
+    let stride = blur.stride() as uint;
+    let offset = radius.width as uint;
+    blur.with_mut_data(|data| {
+        for y in range(offset, offset + 4) {
+            for x in range(offset, offset + 4) {
+                data[y * stride + x] = 0xff;
+            }
+        }
+    });
+    blur.blur();
+
+    blur.with_data(|data| {
+        let center = data[(offset + 2) * stride + offset + 2];
+        let outside = data[(offset - 1) * stride + offset + 2];
+        assert!(center < 0xff);
+        assert!(outside > 0);
+        assert!(center > outside);
+    });
+}
+
+#[test]
+fn box_shadows_follow_css_semantics() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA, Color, CornerRadii};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    fn alpha_at(target: &DrawTarget, x: uint, y: uint) -> u8 {
+        let pixels = target.snapshot().get_data_surface().convert_to(StraightRGBA);
+        *pixels.get((y * 64 + x) * 4 + 3)
+    }
+
+    let rect = Rect(Point2D(16.0, 16.0), Size2D(32.0, 32.0));
+    let black = Color::new(0.0, 0.0, 0.0, 1.0);
+
+    let outer = DrawTarget::new(SkiaBackend, Size2D(64i32, 64i32), B8G8R8A8);
+    outer.draw_box_shadow(&rect, &CornerRadii::uniform(4.0), black, Point2D(4.0, 4.0), 2.0, 2.0,
+                          false);
+    // Nothing is drawn inside the box or far from it, and the shadow fades out at its edge.
+    assert_eq!(alpha_at(&outer, 32, 32), 0);
+    assert_eq!(alpha_at(&outer, 2, 2), 0);
+    assert!(alpha_at(&outer, 50, 32) > 0xe0);
+    assert!(alpha_at(&outer, 54, 32) > 0 && alpha_at(&outer, 54, 32) < 0x80);
+    // The offset moves the shadow down and to the right, so none shows above the box.
+    assert_eq!(alpha_at(&outer, 32, 13), 0);
+
+    let inset = DrawTarget::new(SkiaBackend, Size2D(64i32, 64i32), B8G8R8A8);
+    inset.draw_box_shadow(&rect, &CornerRadii::zero(), black, Point2D(4.0, 4.0), 1.0, 0.0, true);
+    // Nothing is drawn outside the box. Inside, the top and left edges cast the shadow.
+    assert_eq!(alpha_at(&inset, 10, 32), 0);
+    assert!(alpha_at(&inset, 17, 32) > 0xf0);
+    assert!(alpha_at(&inset, 32, 32) < 0x10);
+    assert!(alpha_at(&inset, 46, 32) < 0x10);
+
+    // Shrinking an outer shadow away entirely draws nothing.
+    let empty = DrawTarget::new(SkiaBackend, Size2D(64i32, 64i32), B8G8R8A8);
+    empty.draw_box_shadow(&rect, &CornerRadii::zero(), black, Point2D(0.0, 0.0), 0.0, -20.0,
+                          false);
+    assert_eq!(alpha_at(&empty, 50, 50), 0);
+}
+
+#[test]
+fn data_source_surfaces_resize() {
+    use azure_hl::{DataSourceSurface, B8G8R8A8, A8, ResizeBox, ResizeLanczos3};
+    use geom::size::Size2D;
+
+    // Opaque white and black columns, which a box filter halving the width averages to gray.
+    let surface = DataSourceSurface::new(Size2D(4i32, 2i32), B8G8R8A8);
+    let stride = surface.stride() as uint;
+    surface.with_mut_data(|data| {
+        for y in range(0, 2u) {
+            for x in range(0, 4u) {
+                let value = if x % 2 == 0 { 0xff } else { 0x00 };
+                for channel in range(0, 3u) {
+                    data[y * stride + x * 4 + channel] = value;
+                }
+                data[y * stride + x * 4 + 3] = 0xff;
+            }
+        }
+    });
+
+    let halved = surface.resize(Size2D(2, 1), ResizeBox).unwrap();
+    let size = halved.get_size();
+    assert_eq!((size.width, size.height), (2, 1));
+    halved.with_data(|data| {
+        for pixel in data.slice_to(8).chunks(4) {
+            assert!(pixel[0] >= 0x7e && pixel[0] <= 0x81);
+            assert_eq!(pixel[3], 0xff);
+        }
+    });
+
+    let enlarged = surface.resize(Size2D(8, 4), ResizeLanczos3).unwrap();
+    assert_eq!(enlarged.get_size().width, 8);
+
+    assert!(surface.resize(Size2D(0, 1), ResizeBox).is_none());
+    assert!(DataSourceSurface::new(Size2D(4i32, 4i32), A8).resize(Size2D(2, 2), ResizeBox)
+                                                         .is_none());
+}
+
+#[test]
+fn data_source_surfaces_half_scale() {
+    use azure_hl::{DataSourceSurface, B8G8R8A8, R5G6B5};
+    use geom::size::Size2D;
+
+    // Rows alternating between opaque white and transparent.
+    let surface = DataSourceSurface::new(Size2D(16i32, 16i32), B8G8R8A8);
+    let stride = surface.stride() as uint;
+    surface.with_mut_data(|data| {
+        for y in range(0, 16u) {
+            let value = if y % 2 == 0 { 0xff } else { 0x00 };
+            for byte in data.mut_slice(y * stride, y * stride + 64).mut_iter() {
+                *byte = value;
+            }
+        }
+    });
+
+    // Halving stops while the result is still larger than the requested size.
+    let scaled = surface.half_scale_for_size(Size2D(3, 7)).unwrap();
+    let size = scaled.get_size();
+    assert_eq!((size.width, size.height), (4, 8));
+    let scaled_stride = scaled.stride() as uint;
+    scaled.with_data(|data| {
+        for y in range(0, 8u) {
+            for byte in data.slice(y * scaled_stride, y * scaled_stride + 16).iter() {
+                assert!(*byte >= 0x7f && *byte <= 0x80);
+            }
+        }
+    });
+
+    let copy = surface.half_scale_for_size(Size2D(16, 16)).unwrap();
+    assert_eq!(copy.get_size().width, 16);
+
+    assert!(DataSourceSurface::new(Size2D(4i32, 4i32), R5G6B5).half_scale_for_size(Size2D(1, 1))
+                                                              .is_none());
+
+    // Empty or negative target sizes are rejected rather than halved forever.
+    assert!(surface.half_scale_for_size(Size2D(0, 4)).is_none());
+    assert!(surface.half_scale_for_size(Size2D(4, -1)).is_none());
+    assert!(surface.half_scale_for_size(Size2D(-3, -3)).is_none());
+}
+
+#[test]
+fn good_filter_averages_large_downscales() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, StraightRGBA};
+    use azure_hl::{DrawOptions, DrawSurfaceOptions, Good};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    // A one pixel checkerboard of opaque black and white, drawn at a ninth of its size.
+    let mut pixels = Vec::from_elem(72 * 72 * 4, 0u8);
+    for y in range(0, 72u) {
+        for x in range(0, 72u) {
+            let value = if (x + y) % 2 == 0 { 0xff } else { 0x00 };
+            let offset = (y * 72 + x) * 4;
+            *pixels.get_mut(offset) = value;
+            *pixels.get_mut(offset + 1) = value;
+            *pixels.get_mut(offset + 2) = value;
+            *pixels.get_mut(offset + 3) = 0xff;
+        }
+    }
+
+    let target = DrawTarget::new(SkiaBackend, Size2D(8i32, 8i32), B8G8R8A8);
+    let surface = target.create_source_surface_from_data(pixels.as_slice(),
+                                                         Size2D(72i32, 72i32),
+                                                         72 * 4,
+                                                         B8G8R8A8);
+    target.draw_surface(surface,
+                        Rect(Point2D(0.0, 0.0), Size2D(8.0, 8.0)),
+                        Rect(Point2D(0.0, 0.0), Size2D(72.0, 72.0)),
+                        DrawSurfaceOptions::new(Good, false),
+                        DrawOptions::new(1.0, 0));
+
+    let result = target.snapshot().get_data_surface().convert_to(StraightRGBA);
+    for pixel in result.as_slice().chunks(4) {
+        assert!(pixel[0] >= 0x70 && pixel[0] <= 0x90);
+        assert_eq!(pixel[3], 0xff);
+    }
+}
+
+#[test]
+fn scaled_fonts_reject_bad_truetype_data() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::ScaledFont;
+
+    let garbage = Vec::from_elem(64, 0xabu8);
+    assert!(ScaledFont::from_truetype_data(garbage.as_slice(), 0, 12.0, SkiaBackend).is_none());
+
+    // A collection header that claims a single face, asked for its second one.
+    let collection = vec!(0x74u8, 0x74, 0x63, 0x66, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 16);
+    assert!(ScaledFont::from_truetype_data(collection.as_slice(), 1, 12.0, SkiaBackend).is_none());
+
+    // A collection whose only face has 65535 table records, all pointing at the same 1 MB
+    // table. Copying the face out would take 64 GB, so the overlapping tables are refused.
+    fn push_u32(data: &mut Vec<u8>, value: u32) {
+        data.push_all([(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]);
+    }
+    let (num_tables, table_length) = (0xffffu32, 1024 * 1024u32);
+    let table_offset = 16 + 12 + num_tables * 16;
+    let mut collection = Vec::new();
+    push_u32(&mut collection, 0x74746366);
+    push_u32(&mut collection, 0x00010000);
+    push_u32(&mut collection, 1);
+    push_u32(&mut collection, 16);
+    push_u32(&mut collection, 0x00010000);
+    push_u32(&mut collection, num_tables << 16);
+    push_u32(&mut collection, 0);
+    for tag in range(0, num_tables) {
+        push_u32(&mut collection, tag);
+        push_u32(&mut collection, 0);
+        push_u32(&mut collection, table_offset);
+        push_u32(&mut collection, table_length);
+    }
+    collection.grow(table_length as uint, &0u8);
+    assert!(ScaledFont::from_truetype_data(collection.as_slice(), 0, 12.0, SkiaBackend).is_none());
+}
+
+#[test]
+fn scaled_fonts_load_each_face_of_a_collection() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::ScaledFont;
+
+    // test/fonts/make_test_fonts.py writes these. Glyph 2 is 'A', which is 700 units wide in
+    // face 0 and 900 units wide in face 1, with 1000 units per em.
+    let single = include_bin!("test/fonts/AzureTest.ttf");
+    let collection = include_bin!("test/fonts/AzureTest.ttc");
+
+    let font = ScaledFont::from_truetype_data(single, 0, 10.0, SkiaBackend).unwrap();
+    assert_eq!(font.glyph_advance(2), 7.0);
+
+    let first = ScaledFont::from_truetype_data(collection, 0, 10.0, SkiaBackend).unwrap();
+    assert_eq!(first.glyph_advance(2), 7.0);
+    let second = ScaledFont::from_truetype_data(collection, 1, 10.0, SkiaBackend).unwrap();
+    assert_eq!(second.glyph_advance(2), 9.0);
+
+    assert!(ScaledFont::from_truetype_data(collection, 2, 10.0, SkiaBackend).is_none());
+    assert!(ScaledFont::from_truetype_data(single, 1, 10.0, SkiaBackend).is_none());
+}
+
+#[test]
+fn font_file_data_round_trips() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::ScaledFont;
+
+    let single = include_bin!("test/fonts/AzureTest.ttf");
+    let font = ScaledFont::from_truetype_data(single, 0, 10.0, SkiaBackend).unwrap();
+    let (data, index) = font.font_file_data().unwrap();
+    assert_eq!(data.as_slice(), single);
+    assert_eq!(index, 0);
+    let copy = ScaledFont::from_truetype_data(data.as_slice(), index, 10.0, SkiaBackend).unwrap();
+    assert_eq!(copy.font_file_data().unwrap(), (data, 0));
+
+    // A face taken out of a collection comes back as a font file of its own.
+    let collection = include_bin!("test/fonts/AzureTest.ttc");
+    let font = ScaledFont::from_truetype_data(collection, 1, 10.0, SkiaBackend).unwrap();
+    let (data, index) = font.font_file_data().unwrap();
+    assert_eq!(index, 0);
+    let copy = ScaledFont::from_truetype_data(data.as_slice(), index, 10.0, SkiaBackend).unwrap();
+    assert_eq!(copy.glyph_advance(2), 9.0);
+    assert_eq!(copy.font_file_data().unwrap(), (data, 0));
+}
+
+#[test]
+fn font_data_picks_a_face_of_a_collection() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::{ScaledFont, FontData};
+
+    let collection = include_bin!("test/fonts/AzureTest.ttc");
+    let first = ScaledFont::new(SkiaBackend, FontData(Vec::from_slice(collection), 0), 10.0);
+    assert_eq!(first.glyph_advance(2), 7.0);
+    let second = ScaledFont::new(SkiaBackend, FontData(Vec::from_slice(collection), 1), 10.0);
+    assert_eq!(second.glyph_advance(2), 9.0);
+}
+
+/// Returns the left, top, right and bottom edges of the smallest rectangle holding every pixel of
+/// `target` that isn't fully transparent, or `None` if there are none.
+#[cfg(test)]
+fn ink_bounds(target: &::azure_hl::DrawTarget) -> Option<(uint, uint, uint, uint)> {
+    use azure_hl::StraightRGBA;
+    use std::cmp;
+
+    let width = target.get_size().width as uint;
+    let pixels = target.snapshot().get_data_surface().convert_to(StraightRGBA);
+    let mut bounds = None;
+    for (i, pixel) in pixels.as_slice().chunks(4).enumerate() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let (x, y) = (i % width, i / width);
+        bounds = Some(match bounds {
+            None => (x, y, x + 1, y + 1),
+            Some((left, top, right, bottom)) => {
+                (cmp::min(left, x), cmp::min(top, y),
+                 cmp::max(right, x + 1), cmp::max(bottom, y + 1))
+            }
+        });
+    }
+    bounds
+}
+
+#[test]
+fn glyph_outlines_cover_the_glyph() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, Color, ColorPattern, DrawOptions};
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use scaled_font::{Glyph, ScaledFont};
+
+    // Glyph 2 is a box from 100 to 600 units across and 0 to 700 up, with 1000 units per em.
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    let glyphs = [Glyph::new(2, Point2D(2.0, 10.0))];
+    let pattern = ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0));
+
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    let path = font.glyph_path(glyphs, &target).unwrap();
+    target.fill(&path, &pattern, &DrawOptions::new(1.0, 0));
+    assert_eq!(ink_bounds(&target), Some((3, 3, 8, 10)));
+
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    let builder = target.create_path_builder();
+    assert!(font.append_glyphs_to(glyphs, &builder));
+    target.fill(&builder.finish(), &pattern, &DrawOptions::new(1.0, 0));
+    assert_eq!(ink_bounds(&target), Some((3, 3, 8, 10)));
+}
+
+#[test]
+fn fill_glyphs_draws_glyphs() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, Color, ColorPattern, DrawOptions};
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use scaled_font::{Glyph, ScaledFont};
+
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    let pattern = ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0));
+
+    // Glyph 2 is a box from 100 to 600 units across and 0 to 700 up, and glyph 3 one from 100
+    // to 400 across and 0 to 500 up, with 1000 units per em.
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    target.fill_glyphs(&font,
+                       [Glyph::new(2, Point2D(2.0, 10.0)), Glyph::new(3, Point2D(8.0, 10.0))],
+                       &pattern,
+                       &DrawOptions::new(1.0, 0),
+                       None);
+    assert_eq!(ink_bounds(&target), Some((3, 3, 12, 10)));
+
+    // No glyphs draw nothing.
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    target.fill_glyphs(&font, [], &pattern, &DrawOptions::new(1.0, 0), None);
+    assert_eq!(ink_bounds(&target), None);
+}
+
+#[test]
+fn fonts_can_be_looked_up_by_name() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::{ScaledFont, FontName, BoldFontStyle};
+
+    let font_name = FontName("sans-serif".to_string(), BoldFontStyle);
+    let font = ScaledFont::new(SkiaBackend, font_name, 10.0);
+    assert!(!font.get_ref().is_null());
+    let metrics = font.font_metrics();
+    assert!(metrics.ascent > 0.0);
+    assert!(metrics.descent > 0.0);
+}
+
+#[test]
+fn font_metrics_and_advances_follow_the_font() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::ScaledFont;
+
+    // AzureTest.ttf has 1000 units per em, an ascender of 800, a descender of 200 and a line
+    // gap of 100. Glyph 2 is 700 units wide and glyph 3 is 500.
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    let metrics = font.font_metrics();
+    let close_to = |value: f32, expected: f32| (value - expected).abs() < 0.01;
+    // Both the ascent and the descent are distances, so both are positive.
+    assert!(close_to(metrics.ascent, 8.0));
+    assert!(close_to(metrics.descent, 2.0));
+    assert!(close_to(metrics.line_gap, 1.0));
+
+    assert_eq!(font.glyph_advance(2), 7.0);
+    assert_eq!(font.glyph_advance(3), 5.0);
+    let bounds = font.glyph_bounds(2);
+    assert!(close_to(bounds.origin.y, -7.0));
+    assert!(close_to(bounds.size.height, 7.0));
+
+    // Glyph 0x10002 would be glyph 2 if the id were cut down to 16 bits.
+    assert_eq!(font.glyph_advance(0x10002), 0.0);
+    assert!(font.glyph_bounds(0x10002).size.width == 0.0);
+}
+
+#[test]
+fn fill_text_lays_glyphs_out_by_their_advances() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, Color, ColorPattern, DrawOptions};
+    use azure_hl::StraightRGBA;
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use scaled_font::ScaledFont;
+
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    assert_eq!(font.glyph_ids("AB A"), vec!(2u32, 3, 1, 2));
+    assert_eq!(font.glyph_ids("\u00e9"), vec!(0u32));
+
+    // 'A' is a box from 100 to 600 units across and 0 to 700 up and 700 units wide, and 'B' one
+    // from 100 to 400 across and 0 to 500 up, with 1000 units per em. Drawn together at a size
+    // of 10, they cover from 1 to 6 and from 8 to 11 pixels right of the origin.
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    target.fill_text(&font,
+                     "AB",
+                     Point2D(2.0, 10.0),
+                     &ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0)),
+                     &DrawOptions::new(1.0, 0));
+    assert_eq!(ink_bounds(&target), Some((3, 3, 13, 10)));
+
+    // 'B' starts where 'A' advanced the pen to, leaving the columns between them empty.
+    let pixels = target.snapshot().get_data_surface().convert_to(StraightRGBA);
+    for y in range(0, 16u) {
+        for x in range(8, 10u) {
+            assert_eq!(pixels.as_slice()[(y * 16 + x) * 4 + 3], 0);
+        }
+    }
+}
+
+#[test]
+fn scaled_fonts_can_be_shared_between_tasks() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8};
+    use azure_hl::{Color, ColorPattern, DrawOptions};
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use recording::{RecordedEvents, ScaledFontCreation};
+    use scaled_font::{Glyph, ScaledFont};
+    use std::task::spawn;
+
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    let glyphs = [Glyph::new(2, Point2D(2.0, 10.0))];
+    let pattern = ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0));
+
+    let recorder = DrawEventRecorder::new_memory();
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    let recording = DrawTarget::new_recording(&recorder, &target);
+    recording.fill_glyphs(&font, glyphs, &pattern, &DrawOptions::new(1.0, 0), None);
+
+    // Each task draws with its own clone and drops it.
+    let (sender, receiver) = channel();
+    for _ in range(0, 4u) {
+        let font = font.clone();
+        let sender = sender.clone();
+        spawn(proc() {
+            let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+            target.fill_glyphs(&font,
+                               [Glyph::new(2, Point2D(2.0, 10.0))],
+                               &ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0)),
+                               &DrawOptions::new(1.0, 0),
+                               None);
+            sender.send(ink_bounds(&target));
+        });
+    }
+    for _ in range(0, 4u) {
+        assert_eq!(receiver.recv(), Some((3, 3, 8, 10)));
+    }
+
+    // The original is still good to draw with, and the recorder only recorded it once.
+    recording.fill_glyphs(&font, glyphs, &pattern, &DrawOptions::new(1.0, 0), None);
+    assert_eq!(ink_bounds(&target), Some((3, 3, 8, 10)));
+    let data = recorder.take_recorded_data().unwrap();
+    let creations = RecordedEvents::new(data.as_slice()).filter(|event| {
+        match *event {
+            ScaledFontCreation(..) => true,
+            _ => false,
+        }
+    }).count();
+    assert_eq!(creations, 1);
+}
+
+#[test]
+fn recorders_release_fonts_nothing_else_holds() {
+    use azure_hl::{DrawEventRecorder, DrawTarget, SkiaBackend, B8G8R8A8};
+    use azure_hl::{Color, ColorPattern, DrawOptions};
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use recording::{RecordedEvents, ScaledFontCreation, ScaledFontDestruction};
+    use scaled_font::{Glyph, ScaledFont};
+
+    let recorder = DrawEventRecorder::new_memory();
+    let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+    let recording = DrawTarget::new_recording(&recorder, &target);
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    recording.fill_glyphs(&font,
+                          [Glyph::new(2, Point2D(2.0, 10.0))],
+                          &ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0)),
+                          &DrawOptions::new(1.0, 0),
+                          None);
+
+    // While the font is alive, the recorder holds on to it as well.
+    let mut data = recorder.take_recorded_data().unwrap();
+    let font_ref = RecordedEvents::new(data.as_slice()).filter_map(|event| {
+        match event {
+            ScaledFontCreation(font_ref, _) => Some(font_ref),
+            _ => None,
+        }
+    }).next().unwrap();
+
+    // Once it has the last reference, it lets go and records the destruction.
+    drop(font);
+    let events = recorder.take_recorded_data().unwrap();
+    data.push_all(events.as_slice());
+    let destroyed: Vec<u64> = RecordedEvents::new(data.as_slice()).filter_map(|event| {
+        match event {
+            ScaledFontDestruction(font_ref) => Some(font_ref),
+            _ => None,
+        }
+    }).collect();
+    assert_eq!(destroyed, vec!(font_ref));
+    assert_eq!(recorder.take_recorded_data().unwrap().len(), 0);
+}
+
+#[test]
+fn glyph_rendering_options_for_skia() {
+    use azure_hl::SkiaBackend;
+    use scaled_font::{GlyphRenderingOptions, LightHinting};
+
+    let options = GlyphRenderingOptions::new(SkiaBackend, LightHinting, true).unwrap();
+    assert!(!options.get_ref().is_null());
+}
+
+#[test]
+fn subpixel_positioning_changes_glyph_rendering() {
+    use azure_hl::{DrawTarget, SkiaBackend, B8G8R8A8, Color, ColorPattern, DrawOptions};
+    use azure_hl::StraightRGBA;
+    use geom::point::Point2D;
+    use geom::size::Size2D;
+    use scaled_font::{Glyph, GlyphRenderingOptions, ScaledFont, NoHinting};
+
+    let font = ScaledFont::from_truetype_data(include_bin!("test/fonts/AzureTest.ttf"),
+                                              0,
+                                              10.0,
+                                              SkiaBackend).unwrap();
+    let draw = |subpixel_positioning: bool| {
+        let options = GlyphRenderingOptions::new(SkiaBackend, NoHinting, subpixel_positioning)
+                          .unwrap();
+        let target = DrawTarget::new(SkiaBackend, Size2D(16i32, 16i32), B8G8R8A8);
+        target.fill_glyphs(&font,
+                           [Glyph::new(2, Point2D(2.5, 10.0))],
+                           &ColorPattern::new(Color::new(0.0, 0.0, 0.0, 1.0)),
+                           &DrawOptions::new(1.0, 0),
+                           Some(&options));
+        target.snapshot().get_data_surface().convert_to(StraightRGBA)
+    };
+
+    // Glyph 2's edges fall on whole pixels when its origin is rounded, and halfway through
+    // pixels when it isn't.
+    let rounded = draw(false);
+    let fractional = draw(true);
+    assert!(rounded.as_slice().chunks(4).all(|pixel| pixel[3] == 0 || pixel[3] == 0xff));
+    assert!(fractional.as_slice().chunks(4).any(|pixel| pixel[3] != 0 && pixel[3] != 0xff));
+}
@@ -0,0 +1,177 @@
+#!/usr/bin/env python
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+"""Writes the fonts used by test.rs.
+
+AzureTest.ttf has 1000 units per em, an ascender of 800, a descender of 200
+and a line gap of 100. It maps ' ' to glyph 1, which is blank and 250 units
+wide, 'A' to glyph 2, a 500 x 700 box 700 units wide, and 'B' to glyph 3, a
+300 x 500 box 500 units wide. Glyph 0 is a 400 x 700 box.
+
+AzureTest.ttc holds AzureTest.ttf as face 0 and, as face 1, a copy whose 'A'
+is an 800 x 700 box 900 units wide, so that tests can tell the faces apart.
+"""
+
+import os
+import struct
+
+UNITS_PER_EM = 1000
+ASCENDER = 800
+DESCENDER = -200
+LINE_GAP = 100
+
+
+def box(x0, y0, x1, y1):
+    return [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+
+
+def glyph_data(points):
+    if not points:
+        return b''
+    xs = [x for x, y in points]
+    ys = [y for x, y in points]
+    data = struct.pack('>hhhhh', 1, min(xs), min(ys), max(xs), max(ys))
+    data += struct.pack('>HH', len(points) - 1, 0)
+    data += b'\x01' * len(points)
+    last = 0
+    for x, y in points:
+        data += struct.pack('>h', x - last)
+        last = x
+    last = 0
+    for x, y in points:
+        data += struct.pack('>h', y - last)
+        last = y
+    return data + b'\0' * (-len(data) % 4)
+
+
+def cmap_table(mapping):
+    segments = [(code, code, glyph - code) for code, glyph in sorted(mapping.items())]
+    segments.append((0xffff, 0xffff, 1))
+    count = len(segments)
+    search_range = 2 * 2 ** (count.bit_length() - 1)
+    subtable = struct.pack('>HHHHHHH', 4, 16 + 8 * count, 0, 2 * count, search_range,
+                           search_range.bit_length() - 2, 2 * count - search_range)
+    subtable += b''.join(struct.pack('>H', end) for start, end, delta in segments)
+    subtable += struct.pack('>H', 0)
+    subtable += b''.join(struct.pack('>H', start) for start, end, delta in segments)
+    subtable += b''.join(struct.pack('>H', delta & 0xffff) for start, end, delta in segments)
+    subtable += b''.join(struct.pack('>H', 0) for segment in segments)
+    return struct.pack('>HHHHL', 0, 1, 3, 1, 12) + subtable
+
+
+def name_table(family):
+    names = [(1, family), (2, 'Regular'), (4, family), (6, family.replace(' ', ''))]
+    strings = b''
+    records = b''
+    for name_id, value in names:
+        encoded = value.encode('utf-16-be')
+        records += struct.pack('>HHHHHH', 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack('>HHH', 0, len(names), 6 + len(records)) + records + strings
+
+
+def checksum(data):
+    data += b'\0' * (-len(data) % 4)
+    return sum(struct.unpack('>%dL' % (len(data) // 4), data)) & 0xffffffff
+
+
+def font_tables(family, a_box, a_advance):
+    glyphs = [(box(50, 0, 450, 700), 500),
+              ([], 250),
+              (a_box, a_advance),
+              (box(100, 0, 400, 500), 500)]
+    glyf = b''
+    loca = b''
+    for points, advance in glyphs:
+        loca += struct.pack('>H', len(glyf) // 2)
+        glyf += glyph_data(points)
+    loca += struct.pack('>H', len(glyf) // 2)
+    hmtx = b''.join(struct.pack('>Hh', advance, min([x for x, y in points] or [0]))
+                    for points, advance in glyphs)
+
+    all_points = [point for points, advance in glyphs for point in points]
+    x_min = min(x for x, y in all_points)
+    x_max = max(x for x, y in all_points)
+    y_min = min(y for x, y in all_points)
+    y_max = max(y for x, y in all_points)
+    max_advance = max(advance for points, advance in glyphs)
+
+    head = struct.pack('>LLLLHHqqhhhhHHhhh', 0x00010000, 0x00010000, 0, 0x5f0f3cf5, 0x000b,
+                       UNITS_PER_EM, 0, 0, x_min, y_min, x_max, y_max, 0, 8, 2, 0, 0)
+    hhea = struct.pack('>LhhhHhhhhhh4hhH', 0x00010000, ASCENDER, DESCENDER, LINE_GAP,
+                       max_advance, 0, 0, x_max, 1, 0, 0, 0, 0, 0, 0, 0, len(glyphs))
+    maxp = struct.pack('>LHHHHHHHHHHHHHH', 0x00010000, len(glyphs), 4, 1, 0, 0, 2, 0, 0, 0,
+                       0, 0, 0, 0, 0)
+    os2 = struct.pack('>HhHHH10hh10sLLLL4sHHHhhhHHLL', 1, 500, 400, 5, 0,
+                      650, 700, 0, 140, 650, 700, 0, 480, 50, 250, 0,
+                      b'\0' * 10, 1, 0, 0, 0, b'AZTS', 0x40, 0x20, 0x42,
+                      ASCENDER, DESCENDER, LINE_GAP, ASCENDER, -DESCENDER, 1, 0)
+    post = struct.pack('>LLhhLLLLL', 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0)
+
+    return {b'OS/2': os2, b'cmap': cmap_table({0x20: 1, 0x41: 2, 0x42: 3}), b'glyf': glyf,
+            b'head': head, b'hhea': hhea, b'hmtx': hmtx, b'loca': loca, b'maxp': maxp,
+            b'name': name_table(family), b'post': post}
+
+
+def layout(fonts, header_length):
+    """Lays the tables of each font out after header_length bytes of collection header.
+    Returns the offset of each font's table directory and the bytes that follow the header."""
+    directory_lengths = [12 + 16 * len(tables) for tables in fonts]
+    offset = header_length + sum(directory_lengths)
+    directories = []
+    data = b''
+    for tables in fonts:
+        records = b''
+        for tag in sorted(tables):
+            table = tables[tag]
+            records += struct.pack('>4sLLL', tag, checksum(table), offset + len(data), len(table))
+            data += table + b'\0' * (-len(table) % 4)
+        count = len(tables)
+        search_range = 16 * 2 ** (count.bit_length() - 1)
+        directories.append(struct.pack('>LHHHH', 0x00010000, count, search_range,
+                                       count.bit_length() - 1, 16 * count - search_range) +
+                           records)
+    offsets = []
+    position = header_length
+    for directory in directories:
+        offsets.append(position)
+        position += len(directory)
+    return offsets, b''.join(directories) + data
+
+
+def set_checksum_adjustment(font, head_offset):
+    adjustment = (0xb1b0afba - checksum(font)) & 0xffffffff
+    return font[:head_offset + 8] + struct.pack('>L', adjustment) + font[head_offset + 12:]
+
+
+def head_offset(font, directory_offset):
+    count = struct.unpack('>H', font[directory_offset + 4:directory_offset + 6])[0]
+    for i in range(count):
+        record = directory_offset + 12 + 16 * i
+        tag, _, offset, _ = struct.unpack('>4sLLL', font[record:record + 16])
+        if tag == b'head':
+            return offset
+
+
+def main():
+    directory = os.path.dirname(os.path.abspath(__file__))
+    regular = font_tables('Azure Test', box(100, 0, 600, 700), 700)
+    wide = font_tables('Azure Test Wide', box(50, 0, 850, 700), 900)
+
+    offsets, font = layout([regular], 0)
+    font = set_checksum_adjustment(font, head_offset(font, 0))
+    with open(os.path.join(directory, 'AzureTest.ttf'), 'wb') as f:
+        f.write(font)
+
+    header_length = 12 + 4 * 2
+    offsets, data = layout([regular, wide], header_length)
+    collection = struct.pack('>4sLL', b'ttcf', 0x00010000, 2)
+    collection += b''.join(struct.pack('>L', offset) for offset in offsets) + data
+    with open(os.path.join(directory, 'AzureTest.ttc'), 'wb') as f:
+        f.write(collection)
+
+
+if __name__ == '__main__':
+    main()